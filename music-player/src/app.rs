@@ -1,48 +1,176 @@
-use crate::config::ConfigManager;
+use crate::art::{
+    self, cache::DiskCache, coverart::CoverArtLookup, decode::ArtImage, palette::ArtPalette,
+    store::ArtStore, ArtError,
+};
+use crate::audio::spectrum::Spectrum;
+use crate::audio::{AudioLevel, AudioSink, AudioSinkInput, DEFAULT_SINK, MAX_BOOST_VOLUME};
+use crate::config::{
+    ArtPathList, ClickGesture, ConfigManager, PanelAction, QuietHours, ScrollAction, ScrollAxis,
+    SpectrumSettings, VolumeCap, VolumeCurve, VolumeSource, DEFAULT_ART_CACHE_MAX_MB,
+};
 use crate::music::{MusicController, PlayerInfo};
-use bytes::Bytes;
 use cosmic::app::{Core, Task};
+use cosmic::iced::keyboard::{key::Named, Key, Modifiers};
+use cosmic::iced::mouse;
 use cosmic::iced::platform_specific::shell::wayland::commands::popup::{destroy_popup, get_popup};
 use cosmic::iced::window::Id;
 use cosmic::iced::Limits;
 use cosmic::{Application, Element};
 use mpris::PlaybackStatus;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 mod subscription;
 mod view;
 
+/// Volumes closer than this are treated as equal.
+pub const VOLUME_EPSILON: f64 = 0.005;
+
+/// Longest gap between the presses of a double click on the panel icon.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Touchpad scroll distance, in pixels, per panel scroll step.
+const PIXEL_SCROLL_STEP: f32 = 40.0;
+
 pub struct CosmicAppletMusic {
     core: Core,
     popup: Option<Id>,
     player_info: PlayerInfo,
     music_controller: MusicController,
     config_manager: Option<ConfigManager>,
-    album_art_handle: Option<cosmic::iced::widget::image::Handle>,
-    current_art_url: Option<String>,
+    art_store: ArtStore,
     active_tab: PopupTab,
+    popup_kind: PopupKind,
+    /// Keyboard-focused transport button; `None` until a key is used
+    keyboard_focus: Option<KeyboardFocus>,
     all_players_info: Vec<PlayerInfo>,
-    player_album_arts: std::collections::HashMap<String, cosmic::iced::widget::image::Handle>,
+    /// Bytes used by the on-disk art cache, refreshed when Settings is shown
+    art_cache_usage: Option<u64>,
+    /// Folder being typed into the allowed/denied art folder inputs
+    art_allowed_input: String,
+    art_denied_input: String,
+    /// Players (by bus name) whose audio streams are listed one by one
+    expanded_streams: HashSet<String>,
+    /// Output devices, and the labels of the output dropdown: "Default
+    /// output" followed by each sink's description
+    sinks: Vec<AudioSink>,
+    output_labels: Vec<String>,
+    /// Name of the default sink, controlled by the master volume
+    default_sink: Option<String>,
+    /// Every application stream, listed on the Mixer tab
+    mixer_streams: Vec<AudioSinkInput>,
+    /// Streams the remembered output was applied to, so later moves made
+    /// elsewhere are left alone
+    routed_streams: HashSet<u32>,
+    /// Bus names of all players on the bus at the last poll, shown or not;
+    /// `None` until the first poll, whose players keep their volume
+    known_players: Option<HashSet<String>>,
+    /// Bus names of players that appeared since, waiting for their start or
+    /// last volume until it could be set or they are gone
+    pending_volumes: HashSet<String>,
+    /// Streams whose levels are sampled, only while the Controls tab is open
+    monitored_streams: Vec<u32>,
+    /// Latest peak and RMS level of each monitored stream
+    levels: HashMap<u32, AudioLevel>,
+    /// Visualizer bars of the selected player's loudest stream
+    spectrum: Spectrum,
+    /// Number of the last left press on the panel icon, and whether it still
+    /// waits to tell a single click from a double click
+    panel_clicks: u64,
+    pending_click: bool,
+    /// Touchpad scrolling on the panel icon not yet turned into a step
+    scroll_pixels: (f32, f32),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PopupTab {
     Controls,
+    /// Every application playing audio, MPRIS or not
+    Mixer,
     Settings,
 }
 
-impl Default for CosmicAppletMusic {
-    fn default() -> Self {
+/// Transport button of a player, in the order keyboard focus moves through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportButton {
+    Previous,
+    PlayPause,
+    Next,
+}
+
+impl TransportButton {
+    pub const ALL: [TransportButton; 3] = [
+        TransportButton::Previous,
+        TransportButton::PlayPause,
+        TransportButton::Next,
+    ];
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|b| *b == self).unwrap_or(0)
+    }
+}
+
+/// Keyboard-focused transport button, on the card at this position of the
+/// visible players (always 0 in single-player mode).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyboardFocus {
+    pub card: usize,
+    pub button: TransportButton,
+}
+
+impl KeyboardFocus {
+    /// The focused button, if it is on this card.
+    pub fn button_on(focus: Option<Self>, card: usize) -> Option<TransportButton> {
+        focus.filter(|f| f.card == card).map(|f| f.button)
+    }
+}
+
+/// Which surface the open popup window shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopupKind {
+    /// The tabbed Controls/Settings popup
+    Full,
+    /// The compact right-click quick actions menu
+    ContextMenu,
+}
+
+impl CosmicAppletMusic {
+    /// The applet before the first poll. Built once with its controller,
+    /// whose audio backend thread lives as long as the applet.
+    fn new(
+        core: Core,
+        music_controller: MusicController,
+        config_manager: Option<ConfigManager>,
+    ) -> Self {
         Self {
-            core: Core::default(),
+            core,
             popup: None,
             player_info: PlayerInfo::default(),
-            music_controller: MusicController::new(),
-            config_manager: None,
-            album_art_handle: None,
-            current_art_url: None,
+            music_controller,
+            config_manager,
+            art_store: ArtStore::default(),
             active_tab: PopupTab::Controls,
+            popup_kind: PopupKind::Full,
+            keyboard_focus: None,
             all_players_info: Vec::new(),
-            player_album_arts: std::collections::HashMap::new(),
+            art_cache_usage: None,
+            art_allowed_input: String::new(),
+            art_denied_input: String::new(),
+            expanded_streams: HashSet::new(),
+            sinks: Vec::new(),
+            output_labels: Vec::new(),
+            default_sink: None,
+            mixer_streams: Vec::new(),
+            routed_streams: HashSet::new(),
+            known_players: None,
+            pending_volumes: HashSet::new(),
+            monitored_streams: Vec::new(),
+            levels: HashMap::new(),
+            spectrum: Spectrum::default(),
+            panel_clicks: 0,
+            pending_click: false,
+            scroll_pixels: (0.0, 0.0),
         }
     }
 }
@@ -57,13 +185,19 @@ pub enum Message {
     Previous,
     UpdatePlayerInfo(PlayerInfo),
     FindPlayer,
+    /// Streams or outputs changed on the sound server
+    AudioChanged,
     UpdateStatus(mpris::PlaybackStatus),
     VolumeChanged(f64),
-    ScrollUp,
-    ScrollDown,
-    MiddleClick,
+    ToggleMute,
+    PanelAction(PanelAction),
+    /// Left press on the panel icon
+    PanelClick,
+    /// The double click interval after the left press with this number passed
+    PanelClickTimeout(u64),
+    PanelScroll(mouse::ScrollDelta),
     LoadAlbumArt(String),
-    AlbumArtLoaded(Option<cosmic::iced::widget::image::Handle>),
+    AlbumArtLoaded(String, Result<ArtImage, ArtError>),
     DiscoverPlayers,
     ToggleAutoDetect(bool),
     SelectPlayer(Option<String>),
@@ -72,10 +206,58 @@ pub enum Message {
     NextPlayer(String),
     PreviousPlayer(String),
     VolumeChangedPlayer(String, f64),
-    LoadAlbumArtPlayer(String, String),
-    AlbumArtLoadedPlayer(String, Option<cosmic::iced::widget::image::Handle>),
+    ToggleMutePlayer(String),
+    /// Show or hide the per-stream volume sliders of a player
+    ToggleStreamList(String),
+    StreamVolumeChanged(u32, f64),
+    ToggleStreamMute(u32),
+    /// Move a player's streams to the output at this dropdown position
+    SetPlayerOutput(String, usize),
+    MasterVolumeChanged(f64),
+    ToggleMasterMute,
+    /// Make the sink at this position of `sinks` the default output
+    SetDefaultSink(usize),
     ToggleShowAllPlayers(bool),
     ToggleHideInactive(bool),
+    SetClickBinding(ClickGesture, PanelAction),
+    SetScrollBinding(ScrollAxis, ScrollAction),
+    SetVolumeStep(f64),
+    SetVolumeCurve(VolumeCurve),
+    ToggleVolumeBoost(bool),
+    /// Set the volume source of the player with this identity
+    SetVolumeSource(String, VolumeSource),
+    /// Set or clear the fixed start volume of the player with this identity
+    SetStartVolume(String, Option<f64>),
+    /// Set or clear the volume cap for every player
+    SetVolumeCap(Option<f64>),
+    /// Set or clear the volume cap of the player with this identity
+    SetPlayerVolumeCap(String, Option<f64>),
+    SetQuietHours(QuietHours),
+    /// Read the latest levels of the monitored streams
+    UpdateLevels,
+    SetSpectrum(SpectrumSettings),
+    /// Analyze the latest samples of the selected player for the visualizer
+    UpdateSpectrum,
+    SetSeekStep(u32),
+    /// Run the wrapped message after closing the quick actions menu
+    ContextMenu(Box<Message>),
+    ToggleShuffle,
+    CycleRepeat,
+    PauseAllPlayers,
+    RaisePlayer,
+    OpenSettings,
+    KeyPressed(Key, Modifiers),
+    ClearArtCache,
+    ToggleOnlineArtLookup(bool),
+    ToggleArtOfflineMode(bool),
+    ToggleArtHttpsOnly(bool),
+    ToggleArtPrivateNetworks(bool),
+    ArtPathInput(ArtPathList, String),
+    AddArtPath(ArtPathList),
+    RemoveArtPath(ArtPathList, PathBuf),
+    ResetArtAllowedPaths,
+    ToggleAccentFromArt(bool),
+    ArtCacheUsage(u64),
 }
 
 impl Application for CosmicAppletMusic {
@@ -98,13 +280,11 @@ impl Application for CosmicAppletMusic {
 
     fn init(core: Core, _flags: Self::Flags) -> (Self, Task<Self::Message>) {
         let config_manager = ConfigManager::new().ok();
-        let app = CosmicAppletMusic {
-            core,
-            music_controller: MusicController::new(),
-            config_manager,
-            active_tab: PopupTab::Controls,
-            ..Default::default()
-        };
+        let music_controller = MusicController::new();
+        if let Some(ref config) = config_manager {
+            music_controller.set_volume_sources(config.get_volume_sources().clone());
+        }
+        let app = CosmicAppletMusic::new(core, music_controller, config_manager);
         (
             app,
             Task::batch([
@@ -131,15 +311,21 @@ impl Application for CosmicAppletMusic {
             Message::TogglePopup => self.handle_toggle_popup(),
             Message::PopupClosed(id) => self.handle_popup_closed(id),
             Message::SwitchTab(tab) => self.handle_switch_tab(tab),
-            Message::PlayPause | Message::MiddleClick => self.handle_play_pause(),
-            Message::Next | Message::ScrollUp => self.handle_next(),
-            Message::Previous | Message::ScrollDown => self.handle_previous(),
+            Message::PlayPause => self.handle_play_pause(),
+            Message::Next => self.handle_next(),
+            Message::Previous => self.handle_previous(),
             Message::UpdatePlayerInfo(info) => self.handle_update_player_info(info),
             Message::FindPlayer => self.handle_find_player(),
+            Message::AudioChanged => self.handle_audio_changed(),
             Message::UpdateStatus(status) => self.handle_update_status(status),
             Message::VolumeChanged(volume) => self.handle_volume_changed(volume),
+            Message::ToggleMute => self.handle_toggle_mute(),
+            Message::PanelAction(action) => self.handle_panel_action(action),
+            Message::PanelClick => self.handle_panel_click(),
+            Message::PanelClickTimeout(click) => self.handle_panel_click_timeout(click),
+            Message::PanelScroll(delta) => self.handle_panel_scroll(delta),
             Message::LoadAlbumArt(url) => self.handle_load_album_art(url),
-            Message::AlbumArtLoaded(handle) => self.handle_album_art_loaded(handle),
+            Message::AlbumArtLoaded(url, art) => self.handle_album_art_loaded(url, art),
             Message::DiscoverPlayers => self.handle_discover_players(),
             Message::ToggleAutoDetect(enabled) => self.handle_toggle_auto_detect(enabled),
             Message::SelectPlayer(player) => self.handle_select_player(player),
@@ -150,25 +336,82 @@ impl Application for CosmicAppletMusic {
             Message::VolumeChangedPlayer(ref bus_name, volume) => {
                 self.handle_volume_changed_player(bus_name, volume)
             }
-            Message::LoadAlbumArtPlayer(bus_name, url) => {
-                self.handle_load_album_art_player(bus_name, url)
+            Message::ToggleMutePlayer(ref bus_name) => self.handle_toggle_mute_player(bus_name),
+            Message::ToggleStreamList(bus_name) => self.handle_toggle_stream_list(bus_name),
+            Message::StreamVolumeChanged(index, volume) => {
+                self.handle_stream_volume_changed(index, volume)
             }
-            Message::AlbumArtLoadedPlayer(bus_name, handle) => {
-                self.handle_album_art_loaded_player(bus_name, handle)
+            Message::ToggleStreamMute(index) => self.handle_toggle_stream_mute(index),
+            Message::SetPlayerOutput(ref bus_name, choice) => {
+                self.handle_set_player_output(bus_name, choice)
             }
+            Message::MasterVolumeChanged(volume) => self.handle_master_volume_changed(volume),
+            Message::ToggleMasterMute => self.handle_toggle_master_mute(),
+            Message::SetDefaultSink(position) => self.handle_set_default_sink(position),
             Message::ToggleShowAllPlayers(enabled) => self.handle_toggle_show_all_players(enabled),
             Message::ToggleHideInactive(enabled) => self.handle_toggle_hide_inactive(enabled),
+            Message::SetClickBinding(gesture, action) => {
+                self.handle_set_click_binding(gesture, action)
+            }
+            Message::SetScrollBinding(axis, action) => self.handle_set_scroll_binding(axis, action),
+            Message::SetVolumeStep(step) => self.handle_set_volume_step(step),
+            Message::SetVolumeCurve(curve) => self.handle_set_volume_curve(curve),
+            Message::ToggleVolumeBoost(enabled) => self.handle_toggle_volume_boost(enabled),
+            Message::SetVolumeSource(identity, source) => {
+                self.handle_set_volume_source(&identity, source)
+            }
+            Message::SetStartVolume(identity, volume) => {
+                self.handle_set_start_volume(&identity, volume)
+            }
+            Message::SetVolumeCap(cap) => self.handle_set_volume_cap(cap),
+            Message::SetPlayerVolumeCap(identity, cap) => {
+                self.handle_set_player_volume_cap(&identity, cap)
+            }
+            Message::SetQuietHours(quiet_hours) => self.handle_set_quiet_hours(quiet_hours),
+            Message::UpdateLevels => self.handle_update_levels(),
+            Message::SetSpectrum(spectrum) => self.handle_set_spectrum(spectrum),
+            Message::UpdateSpectrum => self.handle_update_spectrum(),
+            Message::SetSeekStep(secs) => self.handle_set_seek_step(secs),
+            Message::ContextMenu(inner) => self.handle_context_menu_item(*inner),
+            Message::ToggleShuffle => self.handle_toggle_shuffle(),
+            Message::CycleRepeat => self.handle_cycle_repeat(),
+            Message::PauseAllPlayers => self.handle_pause_all_players(),
+            Message::RaisePlayer => self.handle_raise_player(),
+            Message::OpenSettings => self.handle_open_settings(),
+            Message::KeyPressed(key, modifiers) => self.handle_key_pressed(&key, modifiers),
+            Message::ClearArtCache => self.handle_clear_art_cache(),
+            Message::ToggleOnlineArtLookup(enabled) => {
+                self.handle_toggle_online_art_lookup(enabled)
+            }
+            Message::ToggleArtOfflineMode(enabled) => self.handle_toggle_art_offline_mode(enabled),
+            Message::ToggleArtHttpsOnly(enabled) => self.handle_toggle_art_https_only(enabled),
+            Message::ToggleArtPrivateNetworks(enabled) => {
+                self.handle_toggle_art_private_networks(enabled)
+            }
+            Message::ArtPathInput(list, text) => self.handle_art_path_input(list, text),
+            Message::AddArtPath(list) => self.handle_add_art_path(list),
+            Message::RemoveArtPath(list, path) => self.handle_remove_art_path(list, &path),
+            Message::ResetArtAllowedPaths => self.handle_reset_art_allowed_paths(),
+            Message::ToggleAccentFromArt(enabled) => self.handle_toggle_accent_from_art(enabled),
+            Message::ArtCacheUsage(bytes) => self.handle_art_cache_usage(bytes),
         }
     }
 
     fn subscription(&self) -> cosmic::iced::Subscription<Self::Message> {
-        subscription::subscription()
+        subscription::subscription(self)
     }
 }
 
 impl CosmicAppletMusic {
     fn handle_toggle_popup(&mut self) -> Task<Message> {
+        self.keyboard_focus = None;
+        self.toggle_popup(PopupKind::Full)
+    }
+
+    /// Close the open popup, or open a popup of the given kind if none is open.
+    fn toggle_popup(&mut self, kind: PopupKind) -> Task<Message> {
         if let Some(p) = self.popup.take() {
+            self.update_monitored_streams();
             destroy_popup(p)
         } else {
             let Some(main_id) = self.core.main_window_id() else {
@@ -176,15 +419,24 @@ impl CosmicAppletMusic {
             };
             let new_id = Id::unique();
             self.popup.replace(new_id);
+            self.popup_kind = kind;
+            self.update_monitored_streams();
             let mut popup_settings = self
                 .core
                 .applet
                 .get_popup_settings(main_id, new_id, None, None, None);
-            popup_settings.positioner.size_limits = Limits::NONE
-                .max_width(400.0)
-                .min_width(300.0)
-                .min_height(150.0)
-                .max_height(300.0);
+            popup_settings.positioner.size_limits = match kind {
+                PopupKind::Full => Limits::NONE
+                    .max_width(400.0)
+                    .min_width(300.0)
+                    .min_height(150.0)
+                    .max_height(view::view_window::POPUP_MAX_HEIGHT),
+                PopupKind::ContextMenu => Limits::NONE
+                    .max_width(280.0)
+                    .min_width(200.0)
+                    .min_height(100.0)
+                    .max_height(500.0),
+            };
             get_popup(popup_settings)
         }
     }
@@ -194,12 +446,18 @@ impl CosmicAppletMusic {
             self.popup = None;
             // Reset to controls tab when popup closes
             self.active_tab = PopupTab::Controls;
+            self.keyboard_focus = None;
+            self.update_monitored_streams();
         }
         Task::none()
     }
 
     fn handle_switch_tab(&mut self, tab: PopupTab) -> Task<Message> {
         self.active_tab = tab;
+        self.update_monitored_streams();
+        if tab == PopupTab::Settings {
+            return self.refresh_art_cache_usage();
+        }
         Task::none()
     }
 
@@ -234,32 +492,19 @@ impl CosmicAppletMusic {
         Task::done(cosmic::Action::App(Message::FindPlayer))
     }
 
-    fn handle_update_player_info(&mut self, info: PlayerInfo) -> Task<Message> {
-        // Check if album art URL changed
-        let should_load_art = match (&self.current_art_url, &info.art_url) {
-            (None, Some(_new_url)) => true,
-            (Some(old_url), Some(new_url)) => old_url != new_url,
-            (Some(_), None) => {
-                self.album_art_handle = None;
-                self.current_art_url = None;
-                false
-            }
-            (None, None) => false,
-        };
-
-        self.player_info = info.clone();
-
-        if should_load_art {
-            if let Some(url) = info.art_url {
-                self.current_art_url = Some(url.clone());
-                return Task::done(cosmic::Action::App(Message::LoadAlbumArt(url)));
-            }
-        }
-
-        Task::none()
+    fn handle_update_player_info(&mut self, mut info: PlayerInfo) -> Task<Message> {
+        self.apply_online_art_fallback(&mut info);
+        self.enforce_volume_caps(std::slice::from_mut(&mut info), true);
+        self.apply_volume_memory(std::slice::from_mut(&mut info), true);
+        self.player_info = info;
+        self.refresh_audio();
+        self.prune_album_art();
+        self.request_album_art(self.player_info.art_url.clone())
     }
 
     fn handle_find_player(&mut self) -> Task<Message> {
+        self.track_new_players();
+
         // Check if in multi-player mode
         let show_all_players = self
             .config_manager
@@ -297,12 +542,28 @@ impl CosmicAppletMusic {
         Task::done(cosmic::Action::App(Message::UpdatePlayerInfo(info)))
     }
 
+    /// Queue the start volume of players that appeared on the bus, whether
+    /// or not they are shown, and drop players that went away.
+    fn track_new_players(&mut self) {
+        let Some(names) = self.music_controller.player_bus_names() else {
+            return;
+        };
+        if let Some(ref known) = self.known_players {
+            self.pending_volumes
+                .extend(names.difference(known).cloned());
+        }
+        self.pending_volumes
+            .retain(|bus_name| names.contains(bus_name));
+        self.known_players = Some(names);
+    }
+
     fn handle_update_status(&mut self, status: PlaybackStatus) -> Task<Message> {
         self.player_info.status = status;
         Task::none()
     }
 
     fn handle_volume_changed(&mut self, volume: f64) -> Task<Message> {
+        let volume = self.capped_volume(&self.player_info.identity, volume);
         if let Err(e) = self.music_controller.set_volume(volume) {
             eprintln!("Failed to set volume: {e}");
         }
@@ -310,129 +571,109 @@ impl CosmicAppletMusic {
         Task::none()
     }
 
-    #[allow(clippy::unused_self)]
+    fn handle_toggle_mute(&mut self) -> Task<Message> {
+        let muted = !self.player_info.muted;
+        if let Err(e) = self.music_controller.set_mute(muted) {
+            eprintln!("Failed to set mute: {e}");
+            return Task::none();
+        }
+        self.player_info.muted = muted;
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
     fn handle_load_album_art(&mut self, url: String) -> Task<Message> {
+        // Coalesce with an in-flight fetch of the same URL
+        if !self.art_store.begin(&url) {
+            return Task::none();
+        }
+        let options = self.art_load_options();
         Task::perform(
-            async move { Self::load_image_from_url(&url).await },
-            |result| cosmic::Action::App(Message::AlbumArtLoaded(result)),
+            async move {
+                let art = art::load_image_from_url(&url, options).await;
+                (url, art)
+            },
+            |(url, art)| cosmic::Action::App(Message::AlbumArtLoaded(url, art)),
         )
     }
 
-    async fn load_image_from_url(url: &str) -> Option<cosmic::iced::widget::image::Handle> {
-        use std::sync::OnceLock;
-        use std::time::Duration;
-
-        // Maximum image size to prevent memory exhaustion attacks
-        const MAX_IMAGE_SIZE: usize = 10 * 1024 * 1024; // 10MB
-
-        // Reusable HTTP client with timeout and redirect limits
-        static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
-
-        // Handle file:// URLs (common for local album art from players like VLC, Lollypop)
-        if url.starts_with("file://") {
-            let raw_path = url.trim_start_matches("file://");
-
-            // Canonicalize to resolve symlinks and ".." traversal
-            let canonical = match tokio::fs::canonicalize(raw_path).await {
-                Ok(p) => p,
-                Err(e) => {
-                    eprintln!("Album art file not found: {e}");
-                    return None;
-                }
-            };
+    fn handle_album_art_loaded(
+        &mut self,
+        url: String,
+        art: Result<ArtImage, ArtError>,
+    ) -> Task<Message> {
+        // Drop results for art that was replaced while it was loading
+        let still_wanted = self.wanted_art_urls().contains(url.as_str());
+        self.art_store.finish(url, art, still_wanted);
+        Task::none()
+    }
 
-            // Only allow reads from known-safe directories to prevent
-            // arbitrary local file disclosure via malicious MPRIS metadata
-            let allowed = Self::is_safe_album_art_path(&canonical);
-            if !allowed {
-                eprintln!("Album art path rejected: outside allowed directories");
-                return None;
-            }
+    /// Art URLs of every player currently shown, in either mode.
+    fn wanted_art_urls(&self) -> HashSet<&str> {
+        std::iter::once(&self.player_info)
+            .chain(self.all_players_info.iter())
+            .filter_map(|p| p.art_url.as_deref())
+            .collect()
+    }
 
-            match tokio::fs::read(&canonical).await {
-                Ok(bytes) => {
-                    if bytes.len() > MAX_IMAGE_SIZE {
-                        eprintln!("Album art file too large: {} bytes", bytes.len());
-                        return None;
-                    }
-                    Some(cosmic::iced::widget::image::Handle::from_bytes(
-                        Bytes::from(bytes),
-                    ))
-                }
-                Err(e) => {
-                    eprintln!("Failed to load album art file: {e}");
-                    None
-                }
-            }
+    /// Point players without art at an online cover lookup, if enabled.
+    fn apply_online_art_fallback(&self, info: &mut PlayerInfo) {
+        let enabled = self
+            .config_manager
+            .as_ref()
+            .is_some_and(ConfigManager::get_online_art_lookup);
+        if !enabled || info.art_url.is_some() || info.album.is_empty() {
+            return;
         }
-        // Handle HTTP/HTTPS URLs
-        else if url.starts_with("http://") || url.starts_with("https://") {
-            let client = HTTP_CLIENT.get_or_init(|| {
-                reqwest::Client::builder()
-                    .timeout(Duration::from_secs(10))
-                    .connect_timeout(Duration::from_secs(5))
-                    .redirect(reqwest::redirect::Policy::limited(3))
-                    .build()
-                    .unwrap_or_else(|_| reqwest::Client::new())
+        // "Unknown Artist" is the placeholder from extract_player_info
+        if info.artist.is_empty() || info.artist == "Unknown Artist" {
+            return;
+        }
+        info.art_url = art::coverart::lookup_url(&info.artist, &info.album);
+    }
+
+    /// Snapshot of the config that art fetches depend on.
+    fn art_load_options(&self) -> art::LoadOptions {
+        let cover_lookup = self
+            .config_manager
+            .as_ref()
+            .filter(|config| config.get_online_art_lookup())
+            .map(|config| CoverArtLookup {
+                musicbrainz_url: config.get_musicbrainz_url().to_string(),
+                cover_art_archive_url: config.get_cover_art_archive_url().to_string(),
             });
+        art::LoadOptions {
+            cache: self.art_cache(),
+            cover_lookup,
+            sizes: view::view_window::ART_SIZES.to_vec(),
+            scale_factor: self.core.scale_factor(),
+            policy: self
+                .config_manager
+                .as_ref()
+                .map(ConfigManager::get_art_fetch_policy)
+                .unwrap_or_default(),
+            paths: self
+                .config_manager
+                .as_ref()
+                .map(ConfigManager::get_art_path_policy)
+                .unwrap_or_default(),
+        }
+    }
 
-            match client.get(url).send().await {
-                Ok(response) => match response.bytes().await {
-                    Ok(bytes) => {
-                        if bytes.len() > MAX_IMAGE_SIZE {
-                            eprintln!("Album art download too large: {} bytes", bytes.len());
-                            return None;
-                        }
-                        Some(cosmic::iced::widget::image::Handle::from_bytes(bytes))
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to read album art response: {e}");
-                        None
-                    }
-                },
-                Err(e) => {
-                    eprintln!("Failed to fetch album art: {e}");
-                    None
-                }
-            }
-        } else {
-            eprintln!("Unsupported album art URL scheme");
-            None
-        }
-    }
-
-    /// Returns true if the path is in a directory considered safe for album art reads.
-    /// Blocks access to sensitive locations like /etc, /proc, ~/.ssh, etc.
-    fn is_safe_album_art_path(path: &std::path::Path) -> bool {
-        // Safe prefixes: user cache/data dirs, /tmp, common media locations
-        let safe_prefixes: Vec<std::path::PathBuf> = vec![
-            // XDG directories (covers ~/.cache, ~/.local/share, etc.)
-            dirs::cache_dir(),
-            dirs::data_dir(),
-            dirs::data_local_dir(),
-            dirs::runtime_dir(),
-            // Common music/media directories
-            dirs::audio_dir(),
-            dirs::picture_dir(),
-            dirs::home_dir().map(|h| h.join("Music")),
-            dirs::home_dir().map(|h| h.join(".music")),
-            // System temp
-            Some(std::path::PathBuf::from("/tmp")),
-            Some(std::path::PathBuf::from("/var/tmp")),
-        ]
-        .into_iter()
-        .flatten()
-        .collect();
-
-        safe_prefixes.iter().any(|prefix| path.starts_with(prefix))
+    fn prune_album_art(&mut self) {
+        let wanted = std::iter::once(&self.player_info)
+            .chain(self.all_players_info.iter())
+            .filter_map(|p| p.art_url.as_deref())
+            .collect();
+        self.art_store.retain(&wanted);
     }
 
-    fn handle_album_art_loaded(
-        &mut self,
-        handle: Option<cosmic::iced::widget::image::Handle>,
-    ) -> Task<Message> {
-        self.album_art_handle = handle;
-        Task::none()
+    fn request_album_art(&self, url: Option<String>) -> Task<Message> {
+        match url {
+            Some(url) if self.art_store.needs_fetch(&url) => {
+                Task::done(cosmic::Action::App(Message::LoadAlbumArt(url)))
+            }
+            _ => Task::none(),
+        }
     }
 
     fn handle_discover_players(&mut self) -> Task<Message> {
@@ -471,29 +712,26 @@ impl CosmicAppletMusic {
         Task::done(cosmic::Action::App(Message::FindPlayer))
     }
 
-    fn handle_update_all_players_info(&mut self, players_info: Vec<PlayerInfo>) -> Task<Message> {
+    fn handle_update_all_players_info(
+        &mut self,
+        mut players_info: Vec<PlayerInfo>,
+    ) -> Task<Message> {
+        for info in &mut players_info {
+            self.apply_online_art_fallback(info);
+        }
+        self.enforce_volume_caps(&mut players_info, false);
+        self.apply_volume_memory(&mut players_info, false);
+
         // Update the list of all players
         self.all_players_info.clone_from(&players_info);
+        self.refresh_audio();
 
-        // Prune stale album art entries for players that no longer exist
-        let active_bus_names: std::collections::HashSet<&str> =
-            players_info.iter().map(|p| p.bus_name.as_str()).collect();
-        self.player_album_arts
-            .retain(|bus_name, _| active_bus_names.contains(bus_name.as_str()));
-
-        // Load album arts for new players
-        let mut tasks = Vec::new();
-        for player in players_info {
-            if let Some(ref art_url) = player.art_url {
-                if !self.player_album_arts.contains_key(&player.bus_name) {
-                    let bus_name = player.bus_name.clone();
-                    let url = art_url.clone();
-                    tasks.push(Task::done(cosmic::Action::App(
-                        Message::LoadAlbumArtPlayer(bus_name, url),
-                    )));
-                }
-            }
-        }
+        // Forget art no player shows anymore, then load art for new tracks
+        self.prune_album_art();
+        let tasks: Vec<_> = players_info
+            .into_iter()
+            .map(|player| self.request_album_art(player.art_url))
+            .collect();
 
         Task::batch(tasks)
     }
@@ -537,6 +775,12 @@ impl CosmicAppletMusic {
     }
 
     fn handle_volume_changed_player(&mut self, bus_name: &str, volume: f64) -> Task<Message> {
+        let identity = self
+            .all_players_info
+            .iter()
+            .find(|p| p.bus_name == bus_name)
+            .map_or("", |p| p.identity.as_str());
+        let volume = self.capped_volume(identity, volume);
         if let Err(e) = self.music_controller.set_volume_player(bus_name, volume) {
             eprintln!("Failed to set volume for player '{bus_name}': {e}");
         }
@@ -553,30 +797,410 @@ impl CosmicAppletMusic {
         Task::none()
     }
 
-    #[allow(clippy::unused_self)]
-    fn handle_load_album_art_player(&mut self, bus_name: String, url: String) -> Task<Message> {
-        Task::perform(
-            async move {
-                let handle = Self::load_image_from_url(&url).await;
-                (bus_name, handle)
-            },
-            |(bus_name, handle)| {
-                cosmic::Action::App(Message::AlbumArtLoadedPlayer(bus_name, handle))
+    fn handle_toggle_mute_player(&mut self, bus_name: &str) -> Task<Message> {
+        let Some(player) = self
+            .all_players_info
+            .iter_mut()
+            .find(|p| p.bus_name == bus_name)
+        else {
+            return Task::none();
+        };
+        let muted = !player.muted;
+        if let Err(e) = self.music_controller.set_mute_player(bus_name, muted) {
+            eprintln!("Failed to set mute for player '{bus_name}': {e}");
+            return Task::none();
+        }
+        player.muted = muted;
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn handle_set_player_output(&mut self, bus_name: &str, choice: usize) -> Task<Message> {
+        let Some(player) = std::iter::once(&self.player_info)
+            .chain(&self.all_players_info)
+            .find(|p| p.bus_name == bus_name)
+        else {
+            return Task::none();
+        };
+        let identity = player.identity.clone();
+        let indices: Vec<u32> = player.streams.iter().map(|s| s.index).collect();
+
+        // The first entry follows the default output
+        let sink_name = match choice {
+            0 => None,
+            _ => match self.sinks.get(choice - 1) {
+                Some(sink) => Some(sink.name.clone()),
+                None => return Task::none(),
             },
-        )
+        };
+
+        if let Err(e) = self
+            .music_controller
+            .move_streams(&indices, sink_name.as_deref().unwrap_or(DEFAULT_SINK))
+        {
+            eprintln!("Failed to move player '{bus_name}' to another output: {e}");
+        }
+        self.routed_streams.extend(indices);
+
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_player_output(&identity, sink_name) {
+                eprintln!("Failed to save output for player '{identity}': {e}");
+            }
+        }
+
+        Task::done(cosmic::Action::App(Message::FindPlayer))
     }
 
-    fn handle_album_art_loaded_player(
-        &mut self,
-        bus_name: String,
-        handle: Option<cosmic::iced::widget::image::Handle>,
-    ) -> Task<Message> {
-        if let Some(handle) = handle {
-            self.player_album_arts.insert(bus_name, handle);
+    fn handle_master_volume_changed(&mut self, volume: f64) -> Task<Message> {
+        let Some(sink) = self.default_sink_mut() else {
+            return Task::none();
+        };
+        sink.volume = volume;
+        let index = sink.index;
+        if let Err(e) = self.music_controller.set_sink_volume(index, volume) {
+            eprintln!("Failed to set output volume: {e}");
+        }
+        Task::none()
+    }
+
+    fn handle_toggle_master_mute(&mut self) -> Task<Message> {
+        let Some(sink) = self.default_sink_mut() else {
+            return Task::none();
+        };
+        sink.muted = !sink.muted;
+        let (index, muted) = (sink.index, sink.muted);
+        if let Err(e) = self.music_controller.set_sink_mute(index, muted) {
+            eprintln!("Failed to mute output: {e}");
+        }
+        Task::none()
+    }
+
+    fn handle_set_default_sink(&mut self, position: usize) -> Task<Message> {
+        let Some(sink) = self.sinks.get(position) else {
+            return Task::none();
+        };
+        if let Err(e) = self.music_controller.set_default_sink(&sink.name) {
+            eprintln!("Failed to set default output: {e}");
+            return Task::none();
+        }
+        self.default_sink = Some(sink.name.clone());
+        Task::none()
+    }
+
+    /// The output device the master volume controls.
+    fn default_sink(&self) -> Option<&AudioSink> {
+        let name = self.default_sink.as_deref()?;
+        self.sinks.iter().find(|sink| sink.name == name)
+    }
+
+    fn default_sink_mut(&mut self) -> Option<&mut AudioSink> {
+        let name = self.default_sink.as_deref()?;
+        self.sinks.iter_mut().find(|sink| sink.name == name)
+    }
+
+    /// Sample the levels of the shown players' streams while the Controls
+    /// tab of the popup is open, and of no streams otherwise. Also sent while
+    /// the set is unchanged, so monitors the server ended are restarted.
+    fn update_monitored_streams(&mut self) {
+        let showing_controls = self.popup.is_some()
+            && self.popup_kind == PopupKind::Full
+            && self.active_tab == PopupTab::Controls;
+        let show_all_players = self
+            .config_manager
+            .as_ref()
+            .is_some_and(ConfigManager::get_show_all_players);
+        let streams: Vec<u32> = if !showing_controls {
+            Vec::new()
+        } else if show_all_players {
+            self.visible_players()
+                .into_iter()
+                .flat_map(|player| player.streams.iter().map(|s| s.index))
+                .collect()
+        } else {
+            self.player_info.streams.iter().map(|s| s.index).collect()
+        };
+        if streams.is_empty() && self.monitored_streams.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.music_controller.monitor_streams(&streams) {
+            eprintln!("Failed to monitor audio streams: {e}");
+        }
+        if streams.is_empty() {
+            self.levels.clear();
+            self.spectrum.clear();
+        }
+        self.monitored_streams = streams;
+    }
+
+    fn handle_update_levels(&mut self) -> Task<Message> {
+        self.levels = self.music_controller.stream_levels();
+        Task::none()
+    }
+
+    /// Visualizer settings while the selected player's streams are
+    /// monitored and the visualizer is enabled.
+    fn active_spectrum(&self) -> Option<SpectrumSettings> {
+        let config = self.config_manager.as_ref()?;
+        let spectrum = config.get_spectrum();
+        let shown = spectrum.enabled
+            && !config.get_show_all_players()
+            && self
+                .player_info
+                .streams
+                .iter()
+                .any(|s| self.monitored_streams.contains(&s.index));
+        shown.then_some(spectrum)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn handle_update_spectrum(&mut self) -> Task<Message> {
+        let Some(settings) = self.active_spectrum() else {
+            return Task::none();
+        };
+        // The loudest stream stands for the player, e.g. the playing browser tab
+        let loudest = self
+            .player_info
+            .streams
+            .iter()
+            .filter_map(|s| Some((s.index, self.levels.get(&s.index)?.rms)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| index);
+        let Some(samples) = loudest.and_then(|index| self.music_controller.stream_samples(index))
+        else {
+            return Task::none();
+        };
+        self.spectrum.update(
+            &samples,
+            settings.bar_count as usize,
+            settings.smoothing as f32,
+        );
+        Task::none()
+    }
+
+    fn handle_set_spectrum(&mut self, spectrum: SpectrumSettings) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_spectrum(spectrum) {
+                eprintln!("Failed to save visualizer settings: {e}");
+            }
+        }
+        if !spectrum.enabled {
+            self.spectrum.clear();
+        }
+        Task::none()
+    }
+
+    /// Set players that just appeared to their start or last volume, and
+    /// remember the volume of players that were already there.
+    fn apply_volume_memory(&mut self, players: &mut [PlayerInfo], single: bool) {
+        let Some(ref config) = self.config_manager else {
+            return;
+        };
+        let mut changed = Vec::new();
+        for player in players
+            .iter_mut()
+            .filter(|p| !p.bus_name.is_empty() && p.can_control_volume)
+        {
+            if self.pending_volumes.contains(&player.bus_name) {
+                let Some(volume) = config.get_initial_volume(&player.identity) else {
+                    self.pending_volumes.remove(&player.bus_name);
+                    continue;
+                };
+                let volume = self.capped_volume(&player.identity, volume);
+                // Retried on the next poll until it can be set
+                match self.set_volume_of(player, volume, single) {
+                    Ok(()) => {
+                        player.volume = volume;
+                        self.pending_volumes.remove(&player.bus_name);
+                    }
+                    Err(e) => eprintln!(
+                        "Failed to restore volume for player '{}': {e}",
+                        player.identity
+                    ),
+                }
+            } else if !player.muted
+                && !config
+                    .get_player_volume(&player.identity)
+                    .is_some_and(|volume| (volume - player.volume).abs() < VOLUME_EPSILON)
+            {
+                changed.push((player.identity.clone(), player.volume));
+            }
+        }
+
+        if changed.is_empty() {
+            return;
+        }
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_player_volumes(changed) {
+                eprintln!("Failed to save player volumes: {e}");
+            }
         }
+    }
+
+    /// Pull players that raised themselves above their volume cap back down.
+    fn enforce_volume_caps(&self, players: &mut [PlayerInfo], single: bool) {
+        for player in players.iter_mut().filter(|p| p.can_control_volume) {
+            let Some(cap) = self.volume_cap(&player.identity) else {
+                continue;
+            };
+            if player.volume <= cap.limit + VOLUME_EPSILON {
+                continue;
+            }
+            match self.set_volume_of(player, cap.limit, single) {
+                Ok(()) => player.volume = cap.limit,
+                Err(e) => eprintln!("Failed to cap volume for player '{}': {e}", player.identity),
+            }
+        }
+    }
+
+    /// Pull every application stream back under its cap.
+    fn enforce_stream_caps(&mut self) {
+        let players = std::iter::once(&self.player_info).chain(&self.all_players_info);
+        let caps = streams_over_cap(&self.mixer_streams, players, |identity| {
+            self.volume_cap(identity)
+        });
+        for (index, limit) in caps {
+            if let Err(e) = self.music_controller.set_stream_volume(index, limit) {
+                eprintln!("Failed to cap volume for audio stream #{index}: {e}");
+                continue;
+            }
+            if let Some(stream) = self.mixer_streams.iter_mut().find(|s| s.index == index) {
+                stream.volume = limit;
+            }
+        }
+    }
+
+    /// Set a player's volume through the current player in single-player
+    /// mode, or by bus name in multi-player mode.
+    fn set_volume_of(&self, player: &PlayerInfo, volume: f64, single: bool) -> anyhow::Result<()> {
+        if single {
+            self.music_controller.set_volume(volume)
+        } else {
+            self.music_controller
+                .set_volume_player(&player.bus_name, volume)
+        }
+    }
+
+    /// The volume cap in effect for a player right now, if any.
+    fn volume_cap(&self, identity: &str) -> Option<VolumeCap> {
+        self.config_manager
+            .as_ref()?
+            .volume_cap_at(identity, chrono::Local::now().time())
+    }
+
+    fn capped_volume(&self, identity: &str, volume: f64) -> f64 {
+        self.volume_cap(identity)
+            .map_or(volume, |cap| volume.min(cap.limit))
+    }
+
+    /// Reload the output devices and application streams, cap the streams,
+    /// and move streams of players that have a remembered output there, once
+    /// per stream.
+    fn refresh_audio(&mut self) {
+        self.mixer_streams = self.music_controller.sink_inputs();
+        self.enforce_stream_caps();
+        self.sinks = self.music_controller.sinks();
+        self.default_sink = self.music_controller.default_sink();
+        self.output_labels = std::iter::once("Default output".to_string())
+            .chain(self.sinks.iter().map(|sink| sink.description.clone()))
+            .collect();
+
+        self.update_monitored_streams();
+
+        let Some(ref config) = self.config_manager else {
+            return;
+        };
+        for player in std::iter::once(&self.player_info).chain(&self.all_players_info) {
+            let Some(sink) = config
+                .get_player_output(&player.identity)
+                .and_then(|name| self.sinks.iter().find(|sink| sink.name == name))
+            else {
+                continue;
+            };
+            for stream in &player.streams {
+                if !self.routed_streams.insert(stream.index) || stream.sink == sink.index {
+                    continue;
+                }
+                if let Err(e) = self
+                    .music_controller
+                    .move_streams(&[stream.index], &sink.name)
+                {
+                    eprintln!(
+                        "Failed to move audio stream #{} to '{}': {e}",
+                        stream.index, sink.name
+                    );
+                }
+            }
+        }
+    }
+
+    /// Follow a change on the sound server without rediscovering players:
+    /// reload the mixer and refresh the streams the players already own.
+    fn handle_audio_changed(&mut self) -> Task<Message> {
+        self.refresh_audio();
+        let mixer_streams = &self.mixer_streams;
+        for player in std::iter::once(&mut self.player_info).chain(&mut self.all_players_info) {
+            for stream in &mut player.streams {
+                if let Some(current) = mixer_streams.iter().find(|s| s.index == stream.index) {
+                    stream.volume = current.volume;
+                    stream.muted = current.muted;
+                    stream.sink = current.sink;
+                }
+            }
+            if player.volume_source == VolumeSource::Stream && !player.streams.is_empty() {
+                player.volume = player.streams.iter().map(|s| s.volume).fold(0.0, f64::max);
+                player.muted = player.streams.iter().all(|s| s.muted);
+            }
+        }
+        Task::none()
+    }
+
+    fn handle_toggle_stream_list(&mut self, bus_name: String) -> Task<Message> {
+        if !self.expanded_streams.remove(&bus_name) {
+            self.expanded_streams.insert(bus_name);
+        }
+        Task::none()
+    }
+
+    fn handle_stream_volume_changed(&mut self, index: u32, volume: f64) -> Task<Message> {
+        // Streams no player owns still follow the global cap and quiet hours
+        let volume = self.capped_volume(self.stream_owner(index).unwrap_or_default(), volume);
+        if let Err(e) = self.music_controller.set_stream_volume(index, volume) {
+            eprintln!("Failed to set volume for audio stream #{index}: {e}");
+        }
+
+        // Reflect the change right away; the player follows its loudest stream
+        for player in std::iter::once(&mut self.player_info).chain(&mut self.all_players_info) {
+            if let Some(stream) = player.streams.iter_mut().find(|s| s.index == index) {
+                stream.volume = volume;
+                player.volume = player.streams.iter().map(|s| s.volume).fold(0.0, f64::max);
+            }
+        }
+        if let Some(stream) = self.mixer_streams.iter_mut().find(|s| s.index == index) {
+            stream.volume = volume;
+        }
+
         Task::none()
     }
 
+    fn handle_toggle_stream_mute(&mut self, index: u32) -> Task<Message> {
+        let Some(stream) = self.mixer_streams.iter_mut().find(|s| s.index == index) else {
+            return Task::none();
+        };
+        stream.muted = !stream.muted;
+        if let Err(e) = self.music_controller.set_stream_mute(index, stream.muted) {
+            eprintln!("Failed to mute audio stream #{index}: {e}");
+        }
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    /// Identity of the shown player an audio stream was matched to.
+    fn stream_owner(&self, index: u32) -> Option<&str> {
+        std::iter::once(&self.player_info)
+            .chain(&self.all_players_info)
+            .find(|player| player.streams.iter().any(|s| s.index == index))
+            .map(|player| player.identity.as_str())
+    }
+
     fn handle_toggle_show_all_players(&mut self, enabled: bool) -> Task<Message> {
         if let Some(ref mut config) = self.config_manager {
             if let Err(e) = config.set_show_all_players(enabled) {
@@ -605,4 +1229,842 @@ impl CosmicAppletMusic {
         }
         Task::none()
     }
+
+    fn handle_panel_action(&mut self, action: PanelAction) -> Task<Message> {
+        let bindings = self
+            .config_manager
+            .as_ref()
+            .map(ConfigManager::get_panel_bindings)
+            .unwrap_or_default();
+        let target = self.panel_target_player();
+
+        match action {
+            PanelAction::None => Task::none(),
+            PanelAction::TogglePopup => self.handle_toggle_popup(),
+            PanelAction::ContextMenu => self.toggle_popup(PopupKind::ContextMenu),
+            PanelAction::PlayPause => match target {
+                Some(bus_name) => self.handle_play_pause_player(&bus_name),
+                None => self.handle_play_pause(),
+            },
+            PanelAction::Next => match target {
+                Some(bus_name) => self.handle_next_player(&bus_name),
+                None => self.handle_next(),
+            },
+            PanelAction::Previous => match target {
+                Some(bus_name) => self.handle_previous_player(&bus_name),
+                None => self.handle_previous(),
+            },
+            PanelAction::VolumeUp => self.handle_step_volume(bindings.volume_step, target),
+            PanelAction::VolumeDown => self.handle_step_volume(-bindings.volume_step, target),
+            PanelAction::SeekForward => {
+                self.handle_seek(i64::from(bindings.seek_step_secs), target.as_deref())
+            }
+            PanelAction::SeekBackward => {
+                self.handle_seek(-i64::from(bindings.seek_step_secs), target.as_deref())
+            }
+            PanelAction::CyclePlayer => self.handle_cycle_player(true),
+            PanelAction::CyclePlayerBack => self.handle_cycle_player(false),
+        }
+    }
+
+    /// Left press on the panel icon. With a double click bound, the single
+    /// click action waits until no second press followed.
+    fn handle_panel_click(&mut self) -> Task<Message> {
+        let bindings = self
+            .config_manager
+            .as_ref()
+            .map(ConfigManager::get_panel_bindings)
+            .unwrap_or_default();
+        if bindings.double_click == PanelAction::None {
+            return self.handle_panel_action(bindings.click(ClickGesture::Left));
+        }
+        if self.pending_click {
+            self.pending_click = false;
+            return self.handle_panel_action(bindings.double_click);
+        }
+
+        self.panel_clicks += 1;
+        self.pending_click = true;
+        let click = self.panel_clicks;
+        Task::perform(tokio::time::sleep(DOUBLE_CLICK_INTERVAL), move |()| {
+            cosmic::Action::App(Message::PanelClickTimeout(click))
+        })
+    }
+
+    fn handle_panel_click_timeout(&mut self, click: u64) -> Task<Message> {
+        if !self.pending_click || click != self.panel_clicks {
+            return Task::none();
+        }
+        self.pending_click = false;
+        let action = self
+            .config_manager
+            .as_ref()
+            .map(ConfigManager::get_panel_bindings)
+            .unwrap_or_default()
+            .click(ClickGesture::Left);
+        self.handle_panel_action(action)
+    }
+
+    /// Scroll on the panel icon: one step per wheel notch, or per
+    /// [`PIXEL_SCROLL_STEP`] of touchpad scrolling.
+    fn handle_panel_scroll(&mut self, delta: mouse::ScrollDelta) -> Task<Message> {
+        let (x, y) = match delta {
+            mouse::ScrollDelta::Lines { x, y } => (x, y),
+            mouse::ScrollDelta::Pixels { x, y } => {
+                // Start over when the direction reverses
+                let accumulate = |total: f32, delta: f32| {
+                    if total * delta < 0.0 {
+                        delta
+                    } else {
+                        total + delta
+                    }
+                };
+                let (total_x, total_y) = (
+                    accumulate(self.scroll_pixels.0, x),
+                    accumulate(self.scroll_pixels.1, y),
+                );
+                if total_x.abs().max(total_y.abs()) < PIXEL_SCROLL_STEP {
+                    self.scroll_pixels = (total_x, total_y);
+                    return Task::none();
+                }
+                self.scroll_pixels = (0.0, 0.0);
+                (total_x, total_y)
+            }
+        };
+
+        let bindings = self
+            .config_manager
+            .as_ref()
+            .map(ConfigManager::get_panel_bindings)
+            .unwrap_or_default();
+        // Scrolling up or right is the "forward" direction
+        let action = if x.abs() > y.abs() {
+            bindings.scroll(ScrollAxis::Horizontal).resolve(x > 0.0)
+        } else {
+            bindings.scroll(ScrollAxis::Vertical).resolve(y > 0.0)
+        };
+        self.handle_panel_action(action)
+    }
+
+    /// Bus name of the player targeted by panel gestures in multi-player mode.
+    ///
+    /// Prefers the selected player, then the first playing one, then the first
+    /// listed. Returns `None` in single-player mode, where gestures act on the
+    /// controller's current player.
+    fn panel_target_player(&self) -> Option<String> {
+        let show_all_players = self
+            .config_manager
+            .as_ref()
+            .is_some_and(ConfigManager::get_show_all_players);
+        if !show_all_players {
+            return None;
+        }
+
+        let selected = self
+            .config_manager
+            .as_ref()
+            .and_then(ConfigManager::get_selected_player);
+
+        self.all_players_info
+            .iter()
+            .find(|p| selected.as_ref() == Some(&p.identity))
+            .or_else(|| {
+                self.all_players_info
+                    .iter()
+                    .find(|p| p.status == PlaybackStatus::Playing)
+            })
+            .or_else(|| self.all_players_info.first())
+            .map(|p| p.bus_name.clone())
+    }
+
+    /// Move a player's volume by `delta` in slider positions, so scrolling
+    /// follows the same curve as the sliders.
+    fn handle_step_volume(&mut self, delta: f64, target: Option<String>) -> Task<Message> {
+        let player = match target {
+            Some(ref bus_name) => self
+                .all_players_info
+                .iter()
+                .find(|p| &p.bus_name == bus_name),
+            None => Some(&self.player_info),
+        };
+        let (current, max) = player.map_or((0.5, 1.0), |p| {
+            (
+                p.volume,
+                self.max_volume(p.volume_source == VolumeSource::Stream),
+            )
+        });
+
+        let curve = self.volume_curve();
+        let volume = curve
+            .to_volume(curve.to_position(current) + delta)
+            .clamp(0.0, max);
+        match target {
+            Some(bus_name) => self.handle_volume_changed_player(&bus_name, volume),
+            None => self.handle_volume_changed(volume),
+        }
+    }
+
+    fn volume_curve(&self) -> VolumeCurve {
+        self.config_manager
+            .as_ref()
+            .map_or(VolumeCurve::Linear, ConfigManager::get_volume_curve)
+    }
+
+    /// Top of a volume slider: stream volumes may be boosted past 100% when
+    /// enabled, MPRIS volumes never are.
+    fn max_volume(&self, stream_backed: bool) -> f64 {
+        let boost = self
+            .config_manager
+            .as_ref()
+            .is_some_and(ConfigManager::get_volume_boost);
+        if stream_backed && boost {
+            MAX_BOOST_VOLUME
+        } else {
+            1.0
+        }
+    }
+
+    fn handle_seek(&self, seconds: i64, target: Option<&str>) -> Task<Message> {
+        let offset_micros = seconds * 1_000_000;
+        let result = match target {
+            Some(bus_name) => self.music_controller.seek_player(bus_name, offset_micros),
+            None => self.music_controller.seek(offset_micros),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to seek: {e}");
+        }
+        Task::none()
+    }
+
+    /// Select the next (or previous) discovered player in alphabetical order.
+    fn handle_cycle_player(&mut self, forward: bool) -> Task<Message> {
+        let mut identities: Vec<String> = self
+            .music_controller
+            .get_discovered_players()
+            .into_iter()
+            .map(|p| p.identity)
+            .collect();
+        if identities.is_empty() {
+            return Task::none();
+        }
+        identities.sort_by_key(|identity| identity.to_lowercase());
+
+        let current = self
+            .config_manager
+            .as_ref()
+            .and_then(ConfigManager::get_selected_player);
+        let len = identities.len();
+        let next_index = match current.and_then(|c| identities.iter().position(|i| *i == c)) {
+            Some(index) if forward => (index + 1) % len,
+            Some(index) => (index + len - 1) % len,
+            None => 0,
+        };
+
+        self.handle_select_player(Some(identities.swap_remove(next_index)))
+    }
+
+    fn handle_set_click_binding(
+        &mut self,
+        gesture: ClickGesture,
+        action: PanelAction,
+    ) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_click_binding(gesture, action) {
+                eprintln!("Failed to save click binding: {e}");
+            }
+        }
+        Task::none()
+    }
+
+    fn handle_set_scroll_binding(
+        &mut self,
+        axis: ScrollAxis,
+        action: ScrollAction,
+    ) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_scroll_binding(axis, action) {
+                eprintln!("Failed to save scroll binding: {e}");
+            }
+        }
+        Task::none()
+    }
+
+    fn handle_set_volume_curve(&mut self, curve: VolumeCurve) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_volume_curve(curve) {
+                eprintln!("Failed to save volume curve: {e}");
+            }
+        }
+        Task::none()
+    }
+
+    fn handle_toggle_volume_boost(&mut self, enabled: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_volume_boost(enabled) {
+                eprintln!("Failed to save volume boost setting: {e}");
+            }
+        }
+        Task::none()
+    }
+
+    fn handle_set_volume_source(&mut self, identity: &str, source: VolumeSource) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_volume_source(identity, source) {
+                eprintln!("Failed to save volume source for '{identity}': {e}");
+            }
+            self.music_controller
+                .set_volume_sources(config.get_volume_sources().clone());
+        }
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn handle_set_start_volume(&mut self, identity: &str, volume: Option<f64>) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_start_volume(identity, volume) {
+                eprintln!("Failed to save start volume for '{identity}': {e}");
+            }
+        }
+        Task::none()
+    }
+
+    fn handle_set_volume_cap(&mut self, cap: Option<f64>) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_volume_cap(cap) {
+                eprintln!("Failed to save volume cap: {e}");
+            }
+        }
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn handle_set_player_volume_cap(&mut self, identity: &str, cap: Option<f64>) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_player_volume_cap(identity, cap) {
+                eprintln!("Failed to save volume cap for '{identity}': {e}");
+            }
+        }
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn handle_set_quiet_hours(&mut self, quiet_hours: QuietHours) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_quiet_hours(quiet_hours) {
+                eprintln!("Failed to save quiet hours: {e}");
+            }
+        }
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn handle_set_volume_step(&mut self, step: f64) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_volume_step(step) {
+                eprintln!("Failed to save volume step: {e}");
+            }
+        }
+        Task::none()
+    }
+
+    fn handle_set_seek_step(&mut self, secs: u32) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_seek_step_secs(secs) {
+                eprintln!("Failed to save seek step: {e}");
+            }
+        }
+        Task::none()
+    }
+
+    /// Player info for the player targeted by panel gestures and the quick
+    /// actions menu.
+    fn panel_target_info(&self) -> &PlayerInfo {
+        self.panel_target_player()
+            .and_then(|bus_name| {
+                self.all_players_info
+                    .iter()
+                    .find(|p| p.bus_name == bus_name)
+            })
+            .unwrap_or(&self.player_info)
+    }
+
+    /// Accent palette of a player's art, unless tinting is turned off.
+    fn accent_palette(&self, info: &PlayerInfo) -> Option<ArtPalette> {
+        let enabled = self
+            .config_manager
+            .as_ref()
+            .map_or(true, ConfigManager::get_accent_from_art);
+        if !enabled {
+            return None;
+        }
+        self.art_store.palette(info.art_url.as_deref()?)
+    }
+
+    fn handle_context_menu_item(&mut self, message: Message) -> Task<Message> {
+        let close = match self.popup.take() {
+            Some(p) => destroy_popup(p),
+            None => Task::none(),
+        };
+        close.chain(self.update(message))
+    }
+
+    fn handle_toggle_shuffle(&mut self) -> Task<Message> {
+        let result = match self.panel_target_player() {
+            Some(bus_name) => self.music_controller.toggle_shuffle_player(&bus_name),
+            None => self.music_controller.toggle_shuffle(),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to toggle shuffle: {e}");
+        }
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn handle_cycle_repeat(&mut self) -> Task<Message> {
+        let result = match self.panel_target_player() {
+            Some(bus_name) => self.music_controller.cycle_loop_status_player(&bus_name),
+            None => self.music_controller.cycle_loop_status(),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to change repeat mode: {e}");
+        }
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn handle_pause_all_players(&mut self) -> Task<Message> {
+        if let Err(e) = self.music_controller.pause_all() {
+            eprintln!("Failed to pause all players: {e}");
+        }
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn handle_raise_player(&mut self) -> Task<Message> {
+        let result = match self.panel_target_player() {
+            Some(bus_name) => self.music_controller.raise_player(&bus_name),
+            None => self.music_controller.raise(),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to raise player window: {e}");
+        }
+        Task::none()
+    }
+
+    fn handle_open_settings(&mut self) -> Task<Message> {
+        self.active_tab = PopupTab::Settings;
+        let usage = self.refresh_art_cache_usage();
+        if self.popup.is_some() && self.popup_kind == PopupKind::Full {
+            usage
+        } else {
+            Task::batch([self.toggle_popup(PopupKind::Full), usage])
+        }
+    }
+
+    /// Players shown in multi-player mode, honouring the hide-stopped setting.
+    fn visible_players(&self) -> Vec<&PlayerInfo> {
+        let hide_inactive = self
+            .config_manager
+            .as_ref()
+            .is_some_and(ConfigManager::get_hide_inactive_players);
+
+        // Only hide Stopped players, keep Playing and Paused visible
+        self.all_players_info
+            .iter()
+            .filter(|player| !hide_inactive || player.status != PlaybackStatus::Stopped)
+            .collect()
+    }
+
+    /// Bus name of the keyboard-focused player in multi-player mode.
+    fn keyboard_target_player(&self) -> Option<String> {
+        let show_all_players = self
+            .config_manager
+            .as_ref()
+            .is_some_and(ConfigManager::get_show_all_players);
+        if !show_all_players {
+            return None;
+        }
+        let players = self.visible_players();
+        players
+            .get(self.keyboard_focus.map_or(0, |focus| focus.card))
+            .or_else(|| players.first())
+            .map(|p| p.bus_name.clone())
+    }
+
+    /// Handle a key press inside the popup.
+    ///
+    /// Escape closes the popup from any tab; the playback shortcuts only apply
+    /// on the Controls tab.
+    fn handle_key_pressed(&mut self, key: &Key, modifiers: Modifiers) -> Task<Message> {
+        if self.popup.is_none() {
+            return Task::none();
+        }
+        if let Key::Named(Named::Escape) = key {
+            return self.handle_toggle_popup();
+        }
+        if self.popup_kind != PopupKind::Full || self.active_tab != PopupTab::Controls {
+            return Task::none();
+        }
+
+        let bindings = self
+            .config_manager
+            .as_ref()
+            .map(ConfigManager::get_panel_bindings)
+            .unwrap_or_default();
+        let show_all_players = self
+            .config_manager
+            .as_ref()
+            .is_some_and(ConfigManager::get_show_all_players);
+        let card_count = if show_all_players {
+            self.visible_players().len()
+        } else {
+            1
+        };
+        let mut focus = self.keyboard_focus.unwrap_or(KeyboardFocus {
+            card: 0,
+            button: TransportButton::PlayPause,
+        });
+        focus.card = focus.card.min(card_count.saturating_sub(1));
+        self.keyboard_focus = Some(focus);
+        let target = self.keyboard_target_player();
+
+        match key.as_ref() {
+            Key::Named(Named::Space) => match target {
+                Some(bus_name) => self.handle_play_pause_player(&bus_name),
+                None => self.handle_play_pause(),
+            },
+            Key::Named(Named::ArrowLeft) => {
+                self.handle_seek(-i64::from(bindings.seek_step_secs), target.as_deref())
+            }
+            Key::Named(Named::ArrowRight) => {
+                self.handle_seek(i64::from(bindings.seek_step_secs), target.as_deref())
+            }
+            Key::Named(Named::ArrowUp) => self.handle_step_volume(bindings.volume_step, target),
+            Key::Named(Named::ArrowDown) => self.handle_step_volume(-bindings.volume_step, target),
+            Key::Named(Named::Enter) => match (focus.button, target) {
+                (TransportButton::Previous, Some(bus_name)) => {
+                    self.handle_previous_player(&bus_name)
+                }
+                (TransportButton::Previous, None) => self.handle_previous(),
+                (TransportButton::PlayPause, Some(bus_name)) => {
+                    self.handle_play_pause_player(&bus_name)
+                }
+                (TransportButton::PlayPause, None) => self.handle_play_pause(),
+                (TransportButton::Next, Some(bus_name)) => self.handle_next_player(&bus_name),
+                (TransportButton::Next, None) => self.handle_next(),
+            },
+            // Through the buttons of a card, then on to the next card
+            Key::Named(Named::Tab) if card_count > 0 => {
+                let count = card_count * TransportButton::ALL.len();
+                let position = focus.card * TransportButton::ALL.len() + focus.button.index();
+                let next = if modifiers.shift() {
+                    (position + count - 1) % count
+                } else {
+                    (position + 1) % count
+                };
+                self.keyboard_focus = Some(KeyboardFocus {
+                    card: next / TransportButton::ALL.len(),
+                    button: TransportButton::ALL[next % TransportButton::ALL.len()],
+                });
+                Task::none()
+            }
+            Key::Character(c) if c.eq_ignore_ascii_case("n") => match target {
+                Some(bus_name) => self.handle_next_player(&bus_name),
+                None => self.handle_next(),
+            },
+            Key::Character(c) if c.eq_ignore_ascii_case("p") => match target {
+                Some(bus_name) => self.handle_previous_player(&bus_name),
+                None => self.handle_previous(),
+            },
+            Key::Character(c) if c.eq_ignore_ascii_case("m") => match target {
+                Some(bus_name) => self.handle_toggle_mute_player(&bus_name),
+                None => self.handle_toggle_mute(),
+            },
+            Key::Character(c) => match c.parse::<usize>() {
+                Ok(digit @ 1..=9) => self.handle_pick_player(digit - 1),
+                _ => Task::none(),
+            },
+            _ => Task::none(),
+        }
+    }
+
+    /// Pick a player by its zero-based position: focuses a card in multi-player
+    /// mode, selects a discovered player in single-player mode.
+    fn handle_pick_player(&mut self, index: usize) -> Task<Message> {
+        let show_all_players = self
+            .config_manager
+            .as_ref()
+            .is_some_and(ConfigManager::get_show_all_players);
+
+        if show_all_players {
+            if index < self.visible_players().len() {
+                self.keyboard_focus = Some(KeyboardFocus {
+                    card: index,
+                    button: TransportButton::PlayPause,
+                });
+            }
+            return Task::none();
+        }
+
+        let mut identities: Vec<String> = self
+            .music_controller
+            .get_discovered_players()
+            .into_iter()
+            .map(|p| p.identity)
+            .collect();
+        identities.sort_by_key(|identity| identity.to_lowercase());
+
+        if index < identities.len() {
+            self.handle_select_player(Some(identities.swap_remove(index)))
+        } else {
+            Task::none()
+        }
+    }
+
+    /// Disk cache handle built from the configured size limit.
+    fn art_cache(&self) -> Option<DiskCache> {
+        let max_mb = self.config_manager.as_ref().map_or(
+            DEFAULT_ART_CACHE_MAX_MB,
+            ConfigManager::get_art_cache_max_mb,
+        );
+        DiskCache::new(u64::from(max_mb) * 1024 * 1024)
+    }
+
+    fn refresh_art_cache_usage(&self) -> Task<Message> {
+        let Some(cache) = self.art_cache() else {
+            return Task::none();
+        };
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || cache.usage())
+                    .await
+                    .unwrap_or(0)
+            },
+            |bytes| cosmic::Action::App(Message::ArtCacheUsage(bytes)),
+        )
+    }
+
+    fn handle_clear_art_cache(&mut self) -> Task<Message> {
+        let Some(cache) = self.art_cache() else {
+            return Task::none();
+        };
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    if let Err(e) = cache.clear() {
+                        eprintln!("Failed to clear album art cache: {e}");
+                    }
+                    cache.usage()
+                })
+                .await
+                .unwrap_or(0)
+            },
+            |bytes| cosmic::Action::App(Message::ArtCacheUsage(bytes)),
+        )
+    }
+
+    fn handle_art_cache_usage(&mut self, bytes: u64) -> Task<Message> {
+        self.art_cache_usage = Some(bytes);
+        Task::none()
+    }
+
+    fn handle_toggle_online_art_lookup(&mut self, enabled: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_online_art_lookup(enabled) {
+                eprintln!("Failed to save online art lookup setting: {e}");
+            }
+        }
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn handle_toggle_art_offline_mode(&mut self, enabled: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_art_offline_mode(enabled) {
+                eprintln!("Failed to save art offline mode setting: {e}");
+            }
+        }
+        // Art that failed while offline gets another chance
+        if !enabled {
+            return self.retry_album_art();
+        }
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn handle_toggle_art_https_only(&mut self, enabled: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_art_https_only(enabled) {
+                eprintln!("Failed to save HTTPS-only art setting: {e}");
+            }
+        }
+        if !enabled {
+            return self.retry_album_art();
+        }
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn handle_toggle_art_private_networks(&mut self, enabled: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_art_allow_private_networks(enabled) {
+                eprintln!("Failed to save local network art setting: {e}");
+            }
+        }
+        if enabled {
+            return self.retry_album_art();
+        }
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn handle_art_path_input(&mut self, list: ArtPathList, text: String) -> Task<Message> {
+        match list {
+            ArtPathList::Allowed => self.art_allowed_input = text,
+            ArtPathList::Denied => self.art_denied_input = text,
+        }
+        Task::none()
+    }
+
+    fn handle_add_art_path(&mut self, list: ArtPathList) -> Task<Message> {
+        let input = match list {
+            ArtPathList::Allowed => std::mem::take(&mut self.art_allowed_input),
+            ArtPathList::Denied => std::mem::take(&mut self.art_denied_input),
+        };
+        let Some(path) = art::paths::parse_folder(&input) else {
+            eprintln!("Ignoring art folder that is not an absolute path: {input}");
+            return Task::none();
+        };
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.add_art_path(list, path) {
+                eprintln!("Failed to save art folders: {e}");
+            }
+        }
+        self.retry_album_art()
+    }
+
+    fn handle_remove_art_path(&mut self, list: ArtPathList, path: &Path) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.remove_art_path(list, path) {
+                eprintln!("Failed to save art folders: {e}");
+            }
+        }
+        self.retry_album_art()
+    }
+
+    fn handle_reset_art_allowed_paths(&mut self) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.reset_art_allowed_paths() {
+                eprintln!("Failed to save art folders: {e}");
+            }
+        }
+        self.retry_album_art()
+    }
+
+    /// Forget failed art and poll again, after a setting change that may let
+    /// it load now.
+    fn retry_album_art(&mut self) -> Task<Message> {
+        self.art_store.clear_failed();
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn handle_toggle_accent_from_art(&mut self, enabled: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            if let Err(e) = config.set_accent_from_art(enabled) {
+                eprintln!("Failed to save accent color setting: {e}");
+            }
+        }
+        Task::none()
+    }
+}
+
+/// Streams above their cap, with the limit each must come down to.
+///
+/// A stream takes the cap of the player owning it, or the global cap and
+/// quiet hours when no player does. Streams of players whose volume is set
+/// through MPRIS are skipped: that volume is capped already, and capping the
+/// stream as well would multiply the two.
+fn streams_over_cap<'a>(
+    streams: &[AudioSinkInput],
+    players: impl Iterator<Item = &'a PlayerInfo> + Clone,
+    cap: impl Fn(&str) -> Option<VolumeCap>,
+) -> Vec<(u32, f64)> {
+    streams
+        .iter()
+        .filter_map(|stream| {
+            let owner = players
+                .clone()
+                .find(|player| player.streams.iter().any(|s| s.index == stream.index));
+            if owner.is_some_and(|player| player.volume_source == VolumeSource::Mpris) {
+                return None;
+            }
+            let limit = cap(owner.map_or("", |player| player.identity.as_str()))?.limit;
+            (stream.volume > limit + VOLUME_EPSILON).then_some((stream.index, limit))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::music::PlayerStream;
+
+    fn stream(index: u32, volume: f64) -> AudioSinkInput {
+        AudioSinkInput {
+            index,
+            application_name: String::new(),
+            volume,
+            muted: false,
+            sink: 0,
+            process_id: None,
+            icon_name: None,
+        }
+    }
+
+    fn player(identity: &str, volume_source: VolumeSource, streams: &[u32]) -> PlayerInfo {
+        PlayerInfo {
+            identity: identity.to_string(),
+            volume_source,
+            streams: streams
+                .iter()
+                .map(|&index| PlayerStream {
+                    index,
+                    application_name: String::new(),
+                    volume: 1.0,
+                    muted: false,
+                    sink: 0,
+                })
+                .collect(),
+            ..PlayerInfo::default()
+        }
+    }
+
+    /// Spotify is capped at 0.5, everything else at 0.8.
+    fn cap(identity: &str) -> Option<VolumeCap> {
+        Some(VolumeCap {
+            limit: if identity == "Spotify" { 0.5 } else { 0.8 },
+            quiet_hours: false,
+        })
+    }
+
+    #[test]
+    fn streams_take_their_owners_cap() {
+        let streams = [stream(1, 0.9), stream(2, 0.9), stream(3, 0.7)];
+        let players = [player("Spotify", VolumeSource::Stream, &[1, 3])];
+        assert_eq!(
+            streams_over_cap(&streams, players.iter(), cap),
+            vec![(1, 0.5), (2, 0.8), (3, 0.5)]
+        );
+    }
+
+    #[test]
+    fn streams_of_mpris_players_are_not_capped_twice() {
+        // The player's MPRIS volume is capped instead; capping its stream
+        // too would leave it at 0.5 x 0.5
+        let streams = [stream(1, 1.0), stream(2, 1.0)];
+        let players = [player("Spotify", VolumeSource::Mpris, &[1])];
+        assert_eq!(
+            streams_over_cap(&streams, players.iter(), cap),
+            vec![(2, 0.8)]
+        );
+    }
+
+    #[test]
+    fn streams_under_their_cap_are_left_alone() {
+        let streams = [stream(1, 0.8), stream(2, 0.2)];
+        assert_eq!(streams_over_cap(&streams, std::iter::empty(), cap), vec![]);
+        assert_eq!(
+            streams_over_cap(&[stream(3, 1.0)], std::iter::empty(), |_| None),
+            vec![]
+        );
+    }
 }
@@ -1,7 +1,51 @@
-use super::Message;
-use cosmic::iced::time;
+use super::{CosmicAppletMusic, Message};
+use cosmic::iced::{keyboard, stream, time, Subscription};
+use futures::SinkExt;
 use std::time::Duration;
 
-pub fn subscription() -> cosmic::iced::Subscription<Message> {
-    time::every(Duration::from_millis(1000)).map(|_| Message::FindPlayer)
+/// How often level meters are redrawn while the popup is open.
+const LEVEL_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Identifies the audio stream change subscription.
+struct AudioChanges;
+
+pub fn subscription(app: &CosmicAppletMusic) -> Subscription<Message> {
+    let mut subscriptions =
+        vec![time::every(Duration::from_millis(1000)).map(|_| Message::FindPlayer)];
+
+    // Reload the mixer right away when streams change on the sound server;
+    // players are still rediscovered on the timer above
+    if let Some(changes) = app.music_controller.audio_changes() {
+        subscriptions.push(Subscription::run_with_id(
+            std::any::TypeId::of::<AudioChanges>(),
+            stream::channel(1, move |mut output| async move {
+                loop {
+                    changes.notified().await;
+                    if output.send(Message::AudioChanged).await.is_err() {
+                        break;
+                    }
+                }
+            }),
+        ));
+    }
+
+    // Keyboard shortcuts only while the popup is open
+    if app.popup.is_some() {
+        subscriptions.push(keyboard::on_key_press(|key, modifiers| {
+            Some(Message::KeyPressed(key, modifiers))
+        }));
+    }
+
+    // Level meters, only while streams are monitored
+    if !app.monitored_streams.is_empty() {
+        subscriptions.push(time::every(LEVEL_INTERVAL).map(|_| Message::UpdateLevels));
+    }
+
+    // Spectrum visualizer, at most at its configured frame rate
+    if let Some(spectrum) = app.active_spectrum() {
+        let frame = Duration::from_secs(1) / spectrum.max_fps.max(1);
+        subscriptions.push(time::every(frame).map(|_| Message::UpdateSpectrum));
+    }
+
+    Subscription::batch(subscriptions)
 }
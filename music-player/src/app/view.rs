@@ -1,11 +1,11 @@
 use crate::app::{CosmicAppletMusic, Message};
-use crate::config::ConfigManager;
-use cosmic::iced::mouse;
+use crate::config::{ClickGesture, ConfigManager};
 use cosmic::widget::Id;
 use cosmic::Element;
 use mpris::PlaybackStatus;
 use std::sync::LazyLock;
 
+mod accent;
 pub mod view_window;
 
 static AUTOSIZE_MAIN_ID: LazyLock<Id> = LazyLock::new(|| Id::new("autosize-main"));
@@ -51,24 +51,35 @@ pub fn view(app: &CosmicAppletMusic) -> Element<'_, Message> {
         }
     };
 
-    cosmic::widget::autosize::autosize(
-        cosmic::widget::mouse_area(
-            app.core
-                .applet
-                .icon_button(icon.to_str())
-                .on_press_down(Message::TogglePopup),
-        )
-        .on_scroll(|delta| match delta {
-            mouse::ScrollDelta::Lines { y, .. } | mouse::ScrollDelta::Pixels { y, .. } => {
-                if y > 0.0 {
-                    Message::ScrollUp
-                } else {
-                    Message::ScrollDown
-                }
-            }
-        })
-        .on_middle_press(Message::MiddleClick),
-        AUTOSIZE_MAIN_ID.clone(),
-    )
-    .into()
+    let bindings = app
+        .config_manager
+        .as_ref()
+        .map(ConfigManager::get_panel_bindings)
+        .unwrap_or_default();
+
+    let icon_button = match app.accent_palette(app.panel_target_info()) {
+        // Same sizing as `icon_button`, with the icon in the art's accent color
+        Some(palette) => {
+            let (size, _) = app.core.applet.suggested_size(true);
+            cosmic::widget::button::custom(
+                cosmic::widget::icon::from_name(icon.to_str())
+                    .size(size)
+                    .symbolic(true)
+                    .icon()
+                    .class(accent::icon_class(palette)),
+            )
+            .padding(app.core.applet.suggested_padding(true))
+            .class(cosmic::theme::Button::AppletIcon)
+        }
+        None => app.core.applet.icon_button(icon.to_str()),
+    };
+
+    // Left presses and scrolling are sorted out in the app: double clicks
+    // and touchpad scrolling span several events
+    let area = cosmic::widget::mouse_area(icon_button.on_press_down(Message::PanelClick))
+        .on_scroll(Message::PanelScroll)
+        .on_middle_press(Message::PanelAction(bindings.click(ClickGesture::Middle)))
+        .on_right_press(Message::PanelAction(bindings.click(ClickGesture::Right)));
+
+    cosmic::widget::autosize::autosize(area, AUTOSIZE_MAIN_ID.clone()).into()
 }
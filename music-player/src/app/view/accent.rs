@@ -0,0 +1,101 @@
+//! Widget styles tinted with the album art palette.
+//!
+//! Every style resolves its colors against the theme it is drawn with, so the
+//! tint follows light/dark mode switches and keeps the contrast guarantees of
+//! [`ArtPalette::accent`] and [`ArtPalette::tint`].
+
+use crate::art::palette::ArtPalette;
+use cosmic::iced::widget::{container, slider, svg};
+use cosmic::iced::{Background, Border, Color};
+use std::rc::Rc;
+
+/// Background of the whole popup, tinted with the dominant color.
+pub fn popup_style(palette: ArtPalette) -> impl Fn(&cosmic::Theme) -> container::Style {
+    move |theme| {
+        let cosmic = theme.cosmic();
+        let text: Color = cosmic.background.on.into();
+        container::Style {
+            text_color: Some(text),
+            background: Some(Background::Color(
+                palette.tint(cosmic.background.base.into(), text),
+            )),
+            border: Border {
+                radius: cosmic.corner_radii.radius_m.into(),
+                ..Border::default()
+            },
+            ..container::Style::default()
+        }
+    }
+}
+
+/// Card background (like `Container::Card`), tinted with the dominant color.
+pub fn card_style(palette: ArtPalette) -> impl Fn(&cosmic::Theme) -> container::Style {
+    move |theme| {
+        let cosmic = theme.cosmic();
+        let text: Color = cosmic.background.component.on.into();
+        container::Style {
+            text_color: Some(text),
+            background: Some(Background::Color(
+                palette.tint(cosmic.background.component.base.into(), text),
+            )),
+            border: Border {
+                radius: cosmic.corner_radii.radius_s.into(),
+                ..Border::default()
+            },
+            ..container::Style::default()
+        }
+    }
+}
+
+/// Stock slider with the filled part of the rail and the handle in the
+/// accent color.
+pub fn slider_class(palette: ArtPalette) -> cosmic::theme::iced::Slider {
+    let style = move |theme: &cosmic::Theme, status| {
+        let mut style =
+            slider::Catalog::style(theme, &cosmic::theme::iced::Slider::Standard, status);
+        let cosmic = theme.cosmic();
+        let accent =
+            Background::Color(palette.accent(cosmic.background.base.into(), cosmic.is_dark));
+        style.rail.backgrounds.0 = accent;
+        style.handle.background = accent;
+        style
+    };
+    cosmic::theme::iced::Slider::Custom {
+        active: Rc::new(move |theme| style(theme, slider::Status::Active)),
+        hovered: Rc::new(move |theme| style(theme, slider::Status::Hovered)),
+        dragging: Rc::new(move |theme| style(theme, slider::Status::Dragged)),
+    }
+}
+
+/// Symbolic icon drawn in the accent color.
+pub fn icon_class(palette: ArtPalette) -> cosmic::theme::Svg {
+    cosmic::theme::Svg::Custom(Rc::new(move |theme| {
+        let cosmic = theme.cosmic();
+        svg::Style {
+            color: Some(palette.accent(cosmic.background.base.into(), cosmic.is_dark)),
+        }
+    }))
+}
+
+/// Filled part of a level meter in the accent color, from the album art when
+/// there is a palette, faded by `alpha`.
+pub fn meter_style(
+    palette: Option<ArtPalette>,
+    alpha: f32,
+) -> impl Fn(&cosmic::Theme) -> container::Style {
+    move |theme| {
+        let cosmic = theme.cosmic();
+        let accent: Color = match palette {
+            Some(palette) => palette.accent(cosmic.background.base.into(), cosmic.is_dark),
+            None => cosmic.accent_color().into(),
+        };
+        container::Style {
+            background: Some(Background::Color(Color { a: alpha, ..accent })),
+            border: Border {
+                radius: cosmic.corner_radii.radius_xs.into(),
+                ..Border::default()
+            },
+            ..container::Style::default()
+        }
+    }
+}
@@ -1,10 +1,30 @@
-use crate::app::{CosmicAppletMusic, Message, PopupTab};
-use crate::config::ConfigManager;
+use super::accent;
+use crate::app::{
+    CosmicAppletMusic, KeyboardFocus, Message, PopupKind, PopupTab, TransportButton, VOLUME_EPSILON,
+};
+use crate::art::palette::ArtPalette;
+use crate::art::store::ArtState;
+use crate::audio::{StreamMatch, MAX_BOOST_VOLUME};
+use crate::config::{
+    ArtPathList, ClickGesture, ConfigManager, PanelAction, QuietHours, ScrollAction, ScrollAxis,
+    SpectrumSettings, VolumeCurve, VolumeSource,
+};
+use crate::music::DiscoveredPlayer;
 use cosmic::{theme, Element};
-use mpris::PlaybackStatus;
+use mpris::{LoopStatus, PlaybackStatus};
+use std::sync::LazyLock;
 
 /// Size in pixels for album art display (square)
-const ALBUM_ART_SIZE: f32 = 80.0;
+const ALBUM_ART_SIZE: u16 = 80;
+
+/// Size in pixels for compact album art in multi-player cards (square)
+const COMPACT_ART_SIZE: u16 = 48;
+
+/// Logical sizes album art is drawn at, used to pre-scale decoded art.
+pub const ART_SIZES: [u16; 2] = [ALBUM_ART_SIZE, COMPACT_ART_SIZE];
+
+/// Tallest the full popup may grow; taller tab content scrolls.
+pub const POPUP_MAX_HEIGHT: f32 = 600.0;
 
 /// Creates a standardized container for album art with consistent sizing and styling.
 ///
@@ -25,8 +45,8 @@ fn create_album_art_container<'a>(
     content: impl Into<Element<'a, Message>>,
 ) -> Element<'a, Message> {
     cosmic::widget::container(content)
-        .width(cosmic::iced::Length::Fixed(ALBUM_ART_SIZE))
-        .height(cosmic::iced::Length::Fixed(ALBUM_ART_SIZE))
+        .width(cosmic::iced::Length::Fixed(f32::from(ALBUM_ART_SIZE)))
+        .height(cosmic::iced::Length::Fixed(f32::from(ALBUM_ART_SIZE)))
         .align_x(cosmic::iced::alignment::Horizontal::Center)
         .align_y(cosmic::iced::alignment::Vertical::Center)
         .class(cosmic::theme::Container::Card)
@@ -34,6 +54,10 @@ fn create_album_art_container<'a>(
 }
 
 pub fn view_window(app: &CosmicAppletMusic, _id: cosmic::iced::window::Id) -> Element<'_, Message> {
+    if app.popup_kind == PopupKind::ContextMenu {
+        return view_context_menu(app);
+    }
+
     let cosmic::cosmic_theme::Spacing {
         space_s, space_m, ..
     } = theme::active().cosmic().spacing;
@@ -46,6 +70,13 @@ pub fn view_window(app: &CosmicAppletMusic, _id: cosmic::iced::window::Id) -> El
     })
     .on_press(Message::SwitchTab(PopupTab::Controls));
 
+    let mixer_button = cosmic::widget::button::text(if app.active_tab == PopupTab::Mixer {
+        "● Mixer"
+    } else {
+        "○ Mixer"
+    })
+    .on_press(Message::SwitchTab(PopupTab::Mixer));
+
     let settings_button = cosmic::widget::button::text(if app.active_tab == PopupTab::Settings {
         "● Settings"
     } else {
@@ -60,11 +91,17 @@ pub fn view_window(app: &CosmicAppletMusic, _id: cosmic::iced::window::Id) -> El
             cosmic::widget::container(cosmic::widget::horizontal_space())
                 .width(cosmic::iced::Length::Fill),
         )
+        .push(mixer_button)
+        .push(
+            cosmic::widget::container(cosmic::widget::horizontal_space())
+                .width(cosmic::iced::Length::Fill),
+        )
         .push(settings_button);
 
     // Tab content
     let tab_content = match app.active_tab {
         PopupTab::Controls => view_controls_tab(app, space_s.into(), space_m.into()),
+        PopupTab::Mixer => view_mixer_tab(app, space_s.into()),
         PopupTab::Settings => view_settings_tab(app, space_s.into(), space_m.into()),
     };
 
@@ -75,6 +112,21 @@ pub fn view_window(app: &CosmicAppletMusic, _id: cosmic::iced::window::Id) -> El
         .push(cosmic::widget::divider::horizontal::default())
         .push(tab_content);
 
+    // Multi-player mode tints each card with its own art instead
+    let show_all_players = app
+        .config_manager
+        .as_ref()
+        .is_some_and(ConfigManager::get_show_all_players);
+    let mut content = cosmic::widget::container(content);
+    if let Some(palette) = app
+        .accent_palette(&app.player_info)
+        .filter(|_| !show_all_players)
+    {
+        content = content.class(cosmic::theme::Container::custom(accent::popup_style(
+            palette,
+        )));
+    }
+
     app.core
         .applet
         .popup_container(content)
@@ -83,11 +135,89 @@ pub fn view_window(app: &CosmicAppletMusic, _id: cosmic::iced::window::Id) -> El
                 .min_height(350.)
                 .min_width(400.0)
                 .max_width(500.0)
-                .max_height(600.0),
+                .max_height(POPUP_MAX_HEIGHT),
         )
         .into()
 }
 
+/// Renders the right-click quick actions menu.
+///
+/// Offers actions that don't need the full popup:
+/// - Switching the controlled player among the discovered ones
+/// - Toggling shuffle and cycling the repeat mode
+/// - Pausing every player
+/// - Raising the player's own window
+/// - Jumping to the Settings tab
+///
+/// Every item is wrapped in `Message::ContextMenu` so the menu closes first.
+fn view_context_menu(app: &CosmicAppletMusic) -> Element<'_, Message> {
+    let cosmic::cosmic_theme::Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
+
+    let menu_item = |label: String, message: Message| {
+        cosmic::applet::menu_button(cosmic::widget::text::body(label))
+            .on_press(Message::ContextMenu(Box::new(message)))
+    };
+
+    let target = app.panel_target_info();
+    let selected = app
+        .config_manager
+        .as_ref()
+        .and_then(ConfigManager::get_selected_player);
+
+    let mut discovered_players = app.music_controller.get_discovered_players();
+    discovered_players.sort_by_key(|p| p.identity.to_lowercase());
+
+    let mut menu = cosmic::widget::column().padding([space_xxs, 0]);
+
+    for player in discovered_players {
+        let marker = if selected.as_ref() == Some(&player.identity) {
+            "● "
+        } else {
+            "○ "
+        };
+        let status_text = if player.is_active { " ♪" } else { "" };
+        let label = format!("{marker}{}{status_text}", player.identity);
+        menu = menu.push(menu_item(
+            label,
+            Message::SelectPlayer(Some(player.identity)),
+        ));
+    }
+
+    let shuffle_label = if target.shuffle {
+        "Shuffle: On"
+    } else {
+        "Shuffle: Off"
+    };
+    let repeat_label = match target.loop_status {
+        LoopStatus::None => "Repeat: Off",
+        LoopStatus::Track => "Repeat: Track",
+        LoopStatus::Playlist => "Repeat: Playlist",
+    };
+    let raise_label = if target.identity.is_empty() {
+        "Open player window".to_string()
+    } else {
+        format!("Open {}", target.identity)
+    };
+
+    menu = menu
+        .push(cosmic::applet::padded_control(
+            cosmic::widget::divider::horizontal::default(),
+        ))
+        .push(menu_item(shuffle_label.to_string(), Message::ToggleShuffle))
+        .push(menu_item(repeat_label.to_string(), Message::CycleRepeat))
+        .push(menu_item(
+            "Pause all players".to_string(),
+            Message::PauseAllPlayers,
+        ))
+        .push(menu_item(raise_label, Message::RaisePlayer))
+        .push(cosmic::applet::padded_control(
+            cosmic::widget::divider::horizontal::default(),
+        ))
+        .push(menu_item("Settings…".to_string(), Message::OpenSettings));
+
+    app.core.applet.popup_container(menu).into()
+}
+
 /// Renders the Controls tab content for the popup window.
 ///
 /// Displays either:
@@ -101,9 +231,174 @@ pub fn view_window(app: &CosmicAppletMusic, _id: cosmic::iced::window::Id) -> El
 /// * `space_m` - Medium spacing value from theme
 ///
 /// # Returns
-/// An Element containing the Controls tab UI
-#[allow(clippy::too_many_lines)]
+/// A scrollable Element containing the Controls tab UI
 fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Element<'_, Message> {
+    let players = view_players(app, space_s, space_m);
+
+    // System output controls on top, when the sound server is reachable
+    let content = match view_master_volume(app, space_s) {
+        Some(master) => cosmic::widget::column()
+            .spacing(space_m)
+            .push(master)
+            .push(cosmic::widget::divider::horizontal::default())
+            .push(players)
+            .into(),
+        None => players,
+    };
+
+    // Meters and the visualizer can outgrow the popup, so the whole tab scrolls
+    cosmic::widget::scrollable(content).into()
+}
+
+/// Renders the default output selector and its volume slider.
+///
+/// The slider goes up to 150%; the part above 100% is marked as a boost zone.
+fn view_master_volume(app: &CosmicAppletMusic, space_s: f32) -> Option<Element<'_, Message>> {
+    let sink = app.default_sink()?;
+    let selected = app.sinks.iter().position(|s| s.index == sink.index);
+
+    let output_row = cosmic::widget::row()
+        .spacing(space_s)
+        .push(cosmic::widget::icon::from_name("audio-speakers-symbolic").size(16))
+        .push(cosmic::widget::dropdown(
+            app.output_labels.get(1..).unwrap_or_default(),
+            selected,
+            Message::SetDefaultSink,
+        ))
+        .align_y(cosmic::iced::Alignment::Center);
+
+    let icon = if sink.muted {
+        "audio-volume-muted-symbolic"
+    } else {
+        "audio-volume-high-symbolic"
+    };
+    #[allow(clippy::cast_possible_truncation)]
+    let percent = (sink.volume * 100.0).round() as i32;
+    let label = if sink.volume > 1.0 {
+        format!("{percent}% boost")
+    } else {
+        format!("{percent}%")
+    };
+
+    let volume_row = cosmic::widget::row()
+        .spacing(space_s)
+        .push(
+            cosmic::widget::button::icon(cosmic::widget::icon::from_name(icon).size(16))
+                .padding(4)
+                .on_press(Message::ToggleMasterMute),
+        )
+        .push(volume_slider(
+            app,
+            sink.volume,
+            MAX_BOOST_VOLUME,
+            None,
+            Message::MasterVolumeChanged,
+        ))
+        .push(cosmic::widget::text::caption(label))
+        .align_y(cosmic::iced::Alignment::Center);
+
+    Some(
+        cosmic::widget::column()
+            .spacing(space_s)
+            .push(output_row)
+            .push(volume_row)
+            .into(),
+    )
+}
+
+/// Renders the Mixer tab: one row per application stream, including
+/// applications without MPRIS such as games or voice chat.
+///
+/// Streams matched to a shown player name it, since its card controls them too.
+fn view_mixer_tab(app: &CosmicAppletMusic, space_s: f32) -> Element<'_, Message> {
+    if app.mixer_streams.is_empty() {
+        return cosmic::widget::container(
+            cosmic::widget::column()
+                .spacing(space_s)
+                .push(cosmic::widget::icon::from_name("audio-volume-muted-symbolic").size(48))
+                .push(cosmic::widget::text::body(
+                    "No applications are playing audio",
+                ))
+                .align_x(cosmic::iced::Alignment::Center),
+        )
+        .width(cosmic::iced::Length::Fill)
+        .height(cosmic::iced::Length::Fixed(200.0))
+        .align_x(cosmic::iced::alignment::Horizontal::Center)
+        .align_y(cosmic::iced::alignment::Vertical::Center)
+        .into();
+    }
+
+    let mut streams_column = cosmic::widget::column().spacing(space_s);
+
+    for stream in &app.mixer_streams {
+        let index = stream.index;
+        let name = if stream.application_name.is_empty() {
+            format!("Stream #{index}")
+        } else {
+            stream.application_name.clone()
+        };
+        let mut title = cosmic::widget::column().push(cosmic::widget::text::body(name));
+        if let Some(identity) = app.stream_owner(index) {
+            title = title.push(cosmic::widget::text::caption(format!("Player: {identity}")));
+        }
+
+        let mute_icon = if stream.muted {
+            "audio-volume-muted-symbolic"
+        } else {
+            "audio-volume-low-symbolic"
+        };
+        let volume_row = cosmic::widget::row()
+            .spacing(space_s / 2.0)
+            .push(
+                cosmic::widget::button::icon(cosmic::widget::icon::from_name(mute_icon).size(12))
+                    .padding(2)
+                    .on_press(Message::ToggleStreamMute(index)),
+            )
+            .push(volume_slider(
+                app,
+                stream.volume,
+                app.max_volume(true),
+                None,
+                move |v| Message::StreamVolumeChanged(index, v),
+            ))
+            .align_y(cosmic::iced::Alignment::Center);
+
+        let row = cosmic::widget::row()
+            .spacing(space_s)
+            .push(
+                cosmic::widget::icon::from_name(
+                    stream
+                        .icon_name
+                        .as_deref()
+                        .unwrap_or("applications-multimedia-symbolic"),
+                )
+                .size(32),
+            )
+            .push(
+                cosmic::widget::column()
+                    .spacing(2)
+                    .push(title)
+                    .push(volume_row)
+                    .width(cosmic::iced::Length::Fill),
+            )
+            .align_y(cosmic::iced::Alignment::Center);
+
+        streams_column = streams_column.push(
+            cosmic::widget::container(row)
+                .padding(space_s)
+                .class(cosmic::theme::Container::Card)
+                .width(cosmic::iced::Length::Fill),
+        );
+    }
+
+    cosmic::widget::scrollable(streams_column)
+        .height(cosmic::iced::Length::Fixed(450.0))
+        .into()
+}
+
+/// Renders the player part of the Controls tab.
+#[allow(clippy::too_many_lines)]
+fn view_players(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Element<'_, Message> {
     // Check if "show all players" mode is enabled
     let show_all_players = app
         .config_manager
@@ -143,34 +438,44 @@ fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Ele
     // Note: Using ContentFit::Contain instead of Cover ensures the entire image
     // renders within the container while preserving aspect ratio.
     // This fixes GitHub issue #1 where album art loaded but didn't render.
-    let album_cover = if let Some(ref handle) = app.album_art_handle {
-        create_album_art_container(
+    let art_state = app
+        .player_info
+        .art_url
+        .as_deref()
+        .map(|url| app.art_store.state(url, ALBUM_ART_SIZE));
+    let album_cover = match art_state {
+        Some(ArtState::Loaded(handle)) => create_album_art_container(
             cosmic::widget::image(handle.clone())
                 .width(cosmic::iced::Length::Fill)
                 .height(cosmic::iced::Length::Fill)
                 .content_fit(cosmic::iced::ContentFit::Contain),
-        )
-    } else if app.player_info.art_url.is_some() {
+        ),
         // Loading state
-        create_album_art_container(
+        Some(ArtState::Loading) => create_album_art_container(
             cosmic::widget::column()
                 .push(cosmic::widget::icon::from_name("image-loading-symbolic").size(32))
                 .push(cosmic::widget::text::caption("Loading...").size(10))
                 .spacing(4)
                 .align_x(cosmic::iced::Alignment::Center),
-        )
-    } else {
+        ),
         // No art available
-        create_album_art_container(
+        Some(ArtState::Failed(_)) | None => create_album_art_container(
             cosmic::widget::icon::from_name("audio-headphones-symbolic").size(48),
-        )
+        ),
     };
 
-    let song_info = cosmic::widget::column()
+    let mut song_info = cosmic::widget::column()
         .spacing(space_s)
         .push(cosmic::widget::text::title4(&app.player_info.title))
         .push(cosmic::widget::text::body(&app.player_info.artist));
 
+    // Explain art blocked by the folder settings instead of failing silently
+    if let Some(ArtState::Failed(Some(reason))) = art_state {
+        song_info = song_info.push(cosmic::widget::text::caption(format!(
+            "Album art blocked: {reason}"
+        )));
+    }
+
     let info_row = cosmic::widget::row()
         .spacing(space_m)
         .push(album_cover)
@@ -182,41 +487,58 @@ fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Ele
         PlaybackStatus::Paused | PlaybackStatus::Stopped => "media-playback-start-symbolic",
     };
 
+    // Highlight the keyboard-focused transport button
+    let focused = KeyboardFocus::button_on(app.keyboard_focus, 0);
+
     let controls = cosmic::widget::row()
         .spacing(space_m)
         .push(
             cosmic::widget::button::icon(cosmic::widget::icon::from_name(
                 "media-skip-backward-symbolic",
             ))
+            .selected(focused == Some(TransportButton::Previous))
             .on_press(Message::Previous),
         )
         .push(
             cosmic::widget::button::icon(cosmic::widget::icon::from_name(status_icon))
+                .selected(focused == Some(TransportButton::PlayPause))
                 .on_press(Message::PlayPause),
         )
         .push(
             cosmic::widget::button::icon(cosmic::widget::icon::from_name(
                 "media-skip-forward-symbolic",
             ))
+            .selected(focused == Some(TransportButton::Next))
             .on_press(Message::Next),
         )
         .align_y(cosmic::iced::Alignment::Center);
 
     // Volume control
+    let player_slider = volume_slider(
+        app,
+        app.player_info.volume,
+        app.max_volume(app.player_info.volume_source == VolumeSource::Stream),
+        app.accent_palette(&app.player_info),
+        Message::VolumeChanged,
+    );
     let volume_row = cosmic::widget::row()
         .spacing(space_s)
-        .push(cosmic::widget::icon::from_name("audio-volume-low-symbolic").size(16))
         .push(
-            cosmic::widget::slider(0.0..=1.0, app.player_info.volume, Message::VolumeChanged)
-                .step(0.01)
-                .width(cosmic::iced::Length::Fill),
+            cosmic::widget::button::icon(
+                cosmic::widget::icon::from_name(volume_icon(&app.player_info)).size(16),
+            )
+            .padding(4)
+            .on_press(Message::ToggleMute),
         )
+        .push(player_slider)
         .push(cosmic::widget::icon::from_name("audio-volume-high-symbolic").size(16))
         .align_y(cosmic::iced::Alignment::Center);
 
-    cosmic::widget::column()
-        .spacing(space_m)
-        .push(info_row)
+    let mut content = cosmic::widget::column().spacing(space_m).push(info_row);
+    if let Some(spectrum) = view_spectrum(app) {
+        content = content.push(spectrum);
+    }
+    content = content
         .push(cosmic::widget::divider::horizontal::default())
         .push(
             cosmic::widget::container(controls)
@@ -224,10 +546,346 @@ fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Ele
                 .width(cosmic::iced::Length::Fill),
         )
         .push(cosmic::widget::divider::horizontal::default())
-        .push(volume_row)
+        .push(volume_row);
+
+    if let Some(meter) = view_level_meter(app, &app.player_info, 4.0) {
+        content = content.push(meter);
+    }
+    if let Some(cap) = view_volume_cap(app, &app.player_info, 12) {
+        content = content.push(cap);
+    }
+    if let Some(streams) = view_streams(app, &app.player_info, 12) {
+        content = content.push(streams);
+    }
+    if let Some(output) = view_output(app, &app.player_info, 16) {
+        content = content.push(output);
+    }
+    content = content.push(view_volume_source(app, &app.player_info, 12));
+
+    content.into()
+}
+
+/// Names the audio streams a player's volume controls and how they were
+/// found. With several streams, a toggle lists them with their own sliders.
+fn view_streams<'a>(
+    app: &'a CosmicAppletMusic,
+    player: &'a crate::music::PlayerInfo,
+    text_size: u16,
+) -> Option<Element<'a, Message>> {
+    let matched_by = match player.stream_match? {
+        StreamMatch::ProcessId => "matched by process",
+        StreamMatch::Name => "matched by name",
+    };
+    let caption = match player.streams.as_slice() {
+        [] => return None,
+        [stream] => format!(
+            "Audio stream: {} #{} ({matched_by})",
+            stream.application_name, stream.index
+        ),
+        streams => format!("{} audio streams ({matched_by})", streams.len()),
+    };
+    let caption = cosmic::widget::text::caption(caption).size(text_size);
+
+    if player.streams.len() < 2 {
+        return Some(caption.into());
+    }
+
+    let expanded = app.expanded_streams.contains(&player.bus_name);
+    let toggle = cosmic::widget::button::icon(
+        cosmic::widget::icon::from_name(if expanded {
+            "pan-up-symbolic"
+        } else {
+            "pan-down-symbolic"
+        })
+        .size(text_size),
+    )
+    .padding(2)
+    .on_press(Message::ToggleStreamList(player.bus_name.clone()));
+
+    let mut column = cosmic::widget::column().spacing(2).push(
+        cosmic::widget::row()
+            .spacing(4)
+            .push(caption)
+            .push(toggle)
+            .align_y(cosmic::iced::Alignment::Center),
+    );
+
+    if expanded {
+        let palette = app.accent_palette(player);
+        for stream in &player.streams {
+            let index = stream.index;
+            let slider = volume_slider(
+                app,
+                stream.volume,
+                app.max_volume(true),
+                palette,
+                move |v| Message::StreamVolumeChanged(index, v),
+            );
+            column = column.push(
+                cosmic::widget::row()
+                    .spacing(4)
+                    .push(
+                        cosmic::widget::text::caption(format!(
+                            "{} #{}",
+                            stream.application_name, stream.index
+                        ))
+                        .size(text_size)
+                        .width(cosmic::iced::Length::FillPortion(2)),
+                    )
+                    .push(
+                        cosmic::widget::container(slider)
+                            .width(cosmic::iced::Length::FillPortion(3)),
+                    )
+                    .align_y(cosmic::iced::Alignment::Center),
+            );
+        }
+    }
+
+    Some(column.into())
+}
+
+/// Volume source picker for a player, naming the source in use.
+/// Thin bar under the volume slider showing the loudest of a player's
+/// monitored streams: RMS filled, peak in a lighter shade, on a 60 dB scale.
+fn view_level_meter<'a>(
+    app: &CosmicAppletMusic,
+    player: &crate::music::PlayerInfo,
+    height: f32,
+) -> Option<Element<'a, Message>> {
+    let levels: Vec<_> = player
+        .streams
+        .iter()
+        .filter_map(|stream| app.levels.get(&stream.index))
+        .collect();
+    if levels.is_empty() {
+        return None;
+    }
+    let rms = meter_portion(levels.iter().map(|l| l.rms).fold(0.0, f32::max));
+    let peak = meter_portion(levels.iter().map(|l| l.peak).fold(0.0, f32::max)).max(rms);
+    let palette = app.accent_palette(player);
+
+    let segment = |portion: u16, alpha: f32| {
+        cosmic::widget::container(cosmic::widget::horizontal_space())
+            .width(cosmic::iced::Length::FillPortion(portion))
+            .height(cosmic::iced::Length::Fill)
+            .class(cosmic::theme::Container::custom(accent::meter_style(
+                palette, alpha,
+            )))
+    };
+    let mut bar = cosmic::widget::row();
+    if rms > 0 {
+        bar = bar.push(segment(rms, 1.0));
+    }
+    if peak > rms {
+        bar = bar.push(segment(peak - rms, 0.4));
+    }
+    if peak < METER_STEPS {
+        bar = bar.push(
+            cosmic::widget::horizontal_space()
+                .width(cosmic::iced::Length::FillPortion(METER_STEPS - peak)),
+        );
+    }
+
+    Some(
+        cosmic::widget::container(bar)
+            .width(cosmic::iced::Length::Fill)
+            .height(cosmic::iced::Length::Fixed(height))
+            .class(cosmic::theme::Container::custom(accent::meter_style(
+                palette, 0.1,
+            )))
+            .into(),
+    )
+}
+
+/// Resolution of the level meter.
+const METER_STEPS: u16 = 1000;
+
+/// Levels this far below full scale show as an empty meter.
+const METER_RANGE_DB: f32 = 60.0;
+
+/// Share of the meter, out of [`METER_STEPS`], for a linear level.
+fn meter_portion(level: f32) -> u16 {
+    let db = 20.0 * level.max(f32::MIN_POSITIVE).log10();
+    meter_steps((db + METER_RANGE_DB) / METER_RANGE_DB)
+}
+
+/// A 0.0-1.0 share in [`METER_STEPS`].
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn meter_steps(share: f32) -> u16 {
+    (share.clamp(0.0, 1.0) * f32::from(METER_STEPS)).round() as u16
+}
+
+/// Height of the spectrum visualizer in pixels.
+const SPECTRUM_HEIGHT: f32 = 40.0;
+
+/// Spectrum bars of the selected player's stream, under the album art.
+fn view_spectrum<'a>(app: &CosmicAppletMusic) -> Option<Element<'a, Message>> {
+    app.active_spectrum()?;
+    let bars = app.spectrum.bars();
+    if bars.is_empty() {
+        return None;
+    }
+    let palette = app.accent_palette(&app.player_info);
+
+    let mut row = cosmic::widget::row()
+        .spacing(2)
+        .height(cosmic::iced::Length::Fixed(SPECTRUM_HEIGHT));
+    for &height in bars {
+        let filled = meter_steps(height);
+        let mut bar = cosmic::widget::column()
+            .width(cosmic::iced::Length::Fill)
+            .height(cosmic::iced::Length::Fill);
+        if filled < METER_STEPS {
+            bar = bar.push(
+                cosmic::widget::horizontal_space()
+                    .height(cosmic::iced::Length::FillPortion(METER_STEPS - filled)),
+            );
+        }
+        if filled > 0 {
+            bar = bar.push(
+                cosmic::widget::container(cosmic::widget::horizontal_space())
+                    .width(cosmic::iced::Length::Fill)
+                    .height(cosmic::iced::Length::FillPortion(filled))
+                    .class(cosmic::theme::Container::custom(accent::meter_style(
+                        palette, 0.8,
+                    ))),
+            );
+        }
+        row = row.push(bar);
+    }
+    Some(row.into())
+}
+
+/// Note under the volume slider while a cap holds the player's volume down.
+fn view_volume_cap<'a>(
+    app: &CosmicAppletMusic,
+    player: &crate::music::PlayerInfo,
+    text_size: u16,
+) -> Option<Element<'a, Message>> {
+    let cap = app.volume_cap(&player.identity)?;
+    if !player.can_control_volume || player.volume < cap.limit - VOLUME_EPSILON {
+        return None;
+    }
+    let reason = if cap.quiet_hours {
+        " (quiet hours)"
+    } else {
+        ""
+    };
+
+    Some(
+        cosmic::widget::row()
+            .spacing(4)
+            .push(cosmic::widget::icon::from_name("dialog-information-symbolic").size(text_size))
+            .push(
+                cosmic::widget::text::caption(format!(
+                    "Volume limited to {:.0}%{reason}",
+                    cap.limit * 100.0
+                ))
+                .size(text_size),
+            )
+            .align_y(cosmic::iced::Alignment::Center)
+            .into(),
+    )
+}
+
+fn view_volume_source<'a>(
+    app: &CosmicAppletMusic,
+    player: &crate::music::PlayerInfo,
+    text_size: u16,
+) -> Element<'a, Message> {
+    let configured = app
+        .config_manager
+        .as_ref()
+        .and_then(|config| config.get_volume_sources().get(&player.identity).copied())
+        .unwrap_or(VolumeSource::Auto);
+    let in_use = match (player.volume_source, player.can_control_volume) {
+        (VolumeSource::Stream, false) => "Volume: no audio stream found",
+        (VolumeSource::Stream, true) => "Volume: audio stream",
+        _ => "Volume: MPRIS",
+    };
+    let identity = player.identity.clone();
+
+    cosmic::widget::row()
+        .spacing(4)
+        .push(
+            cosmic::widget::text::caption(in_use)
+                .size(text_size)
+                .width(cosmic::iced::Length::Fill),
+        )
+        .push(cosmic::widget::dropdown(
+            &VolumeSource::LABELS,
+            Some(configured.index()),
+            move |index| Message::SetVolumeSource(identity.clone(), VolumeSource::ALL[index]),
+        ))
+        .align_y(cosmic::iced::Alignment::Center)
         .into()
 }
 
+/// Output device picker for a player with audio streams, showing the device
+/// its first stream plays on.
+fn view_output<'a>(
+    app: &'a CosmicAppletMusic,
+    player: &crate::music::PlayerInfo,
+    icon_size: u16,
+) -> Option<Element<'a, Message>> {
+    let current = player.streams.first()?.sink;
+    let selected = app
+        .sinks
+        .iter()
+        .position(|sink| sink.index == current)
+        .map(|position| position + 1);
+    let bus_name = player.bus_name.clone();
+
+    Some(
+        cosmic::widget::row()
+            .spacing(4)
+            .push(cosmic::widget::icon::from_name("audio-speakers-symbolic").size(icon_size))
+            .push(cosmic::widget::dropdown(
+                &app.output_labels,
+                selected,
+                move |choice| Message::SetPlayerOutput(bus_name.clone(), choice),
+            ))
+            .align_y(cosmic::iced::Alignment::Center)
+            .into(),
+    )
+}
+
+/// Volume slider following the configured curve, from 0 to `max` (1.0 =
+/// 100%). Past 100% a mark at 100% sets off the boost zone.
+fn volume_slider<'a>(
+    app: &CosmicAppletMusic,
+    volume: f64,
+    max: f64,
+    palette: Option<ArtPalette>,
+    on_change: impl Fn(f64) -> Message + 'a,
+) -> Element<'a, Message> {
+    let curve = app.volume_curve();
+    let end = curve.to_position(max);
+    let mut slider = cosmic::widget::slider(
+        0.0..=end,
+        curve.to_position(volume).min(end),
+        move |position| on_change(curve.to_volume(position).min(max)),
+    )
+    .step(0.01)
+    .width(cosmic::iced::Length::Fill);
+    if max > 1.0 {
+        slider = slider.breakpoints(&[1.0]);
+    }
+    if let Some(palette) = palette {
+        slider = slider.class(accent::slider_class(palette));
+    }
+    slider.into()
+}
+
+/// Icon of the mute button in front of a volume slider.
+fn volume_icon(player: &crate::music::PlayerInfo) -> &'static str {
+    if player.muted {
+        "audio-volume-muted-symbolic"
+    } else {
+        "audio-volume-low-symbolic"
+    }
+}
+
 /// Renders the Settings tab content for the popup window.
 ///
 /// Provides configuration options for:
@@ -236,6 +894,8 @@ fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Ele
 /// - Auto-detect new players toggle
 /// - Manual player discovery button
 /// - Player selection (single-player mode only)
+/// - Panel icon mouse and gesture bindings
+/// - Online cover lookup (privacy opt-in), album art cache usage and clearing
 ///
 /// # Arguments
 /// * `app` - Application state containing configuration and discovered players
@@ -244,7 +904,7 @@ fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Ele
 ///
 /// # Returns
 /// A scrollable Element containing the Settings tab UI
-#[allow(clippy::too_many_lines)]
+#[allow(clippy::too_many_lines, clippy::cast_precision_loss)]
 fn view_settings_tab(app: &CosmicAppletMusic, _space_s: f32, space_m: f32) -> Element<'_, Message> {
     // Get discovered players
     let discovered_players = app.music_controller.get_discovered_players();
@@ -391,12 +1051,475 @@ fn view_settings_tab(app: &CosmicAppletMusic, _space_s: f32, space_m: f32) -> El
         }
     }
 
+    if let Some(ref config) = app.config_manager {
+        settings_content = settings_content
+            .push(cosmic::widget::divider::horizontal::default())
+            .push(view_panel_bindings(config, space_m))
+            .push(cosmic::widget::divider::horizontal::default())
+            .push(view_volume_settings(config, &discovered_players, space_m))
+            .push(cosmic::widget::divider::horizontal::default())
+            .push(view_spectrum_settings(config, space_m));
+    }
+
+    // Album art cache section
+    let cache_usage = match app.art_cache_usage {
+        Some(bytes) => format!(
+            "Cached album art uses {:.1} MB of {} MB",
+            bytes as f64 / (1024.0 * 1024.0),
+            app.config_manager.as_ref().map_or(
+                crate::config::DEFAULT_ART_CACHE_MAX_MB,
+                ConfigManager::get_art_cache_max_mb
+            )
+        ),
+        None => "Calculating cache size...".to_string(),
+    };
+
+    settings_content = settings_content
+        .push(cosmic::widget::divider::horizontal::default())
+        .push(cosmic::widget::text::title4("Album Art"));
+
+    if let Some(ref config) = app.config_manager {
+        settings_content = settings_content
+            .push(
+                cosmic::widget::checkbox(
+                    "Look up missing covers online",
+                    config.get_online_art_lookup(),
+                )
+                .on_toggle(Message::ToggleOnlineArtLookup),
+            )
+            .push(cosmic::widget::text::caption(format!(
+                "Sends artist and album names to {}",
+                config.get_musicbrainz_url()
+            )));
+
+        let policy = config.get_art_fetch_policy();
+        settings_content = settings_content
+            .push(
+                cosmic::widget::checkbox("Offline mode (cached art only)", policy.offline)
+                    .on_toggle(Message::ToggleArtOfflineMode),
+            )
+            .push(
+                cosmic::widget::checkbox("Only download art over HTTPS", policy.https_only)
+                    .on_toggle(Message::ToggleArtHttpsOnly),
+            )
+            .push(
+                cosmic::widget::checkbox(
+                    "Allow art from the local network",
+                    policy.allow_private_networks,
+                )
+                .on_toggle(Message::ToggleArtPrivateNetworks),
+            )
+            .push(
+                cosmic::widget::checkbox(
+                    "Tint with colors from the album art",
+                    config.get_accent_from_art(),
+                )
+                .on_toggle(Message::ToggleAccentFromArt),
+            )
+            .push(cosmic::widget::text::body("Local art folders"))
+            .push(view_art_folders(app, config, ArtPathList::Allowed, space_m))
+            .push(
+                cosmic::widget::button::text("Reset to default folders")
+                    .on_press(Message::ResetArtAllowedPaths),
+            )
+            .push(cosmic::widget::text::body("Blocked folders"))
+            .push(cosmic::widget::text::caption(
+                "Never read art from these, even inside an allowed folder",
+            ))
+            .push(view_art_folders(app, config, ArtPathList::Denied, space_m));
+    }
+
+    settings_content = settings_content
+        .push(cosmic::widget::text::caption(cache_usage))
+        .push(
+            cosmic::widget::button::text("Clear art cache")
+                .on_press(Message::ClearArtCache)
+                .width(cosmic::iced::Length::Fill),
+        );
+
     cosmic::widget::scrollable(settings_content).into()
 }
 
+/// Renders one of the local art folder lists with remove buttons, followed by
+/// an input to add a folder.
+fn view_art_folders<'a>(
+    app: &'a CosmicAppletMusic,
+    config: &ConfigManager,
+    list: ArtPathList,
+    space_m: f32,
+) -> Element<'a, Message> {
+    let mut folders = cosmic::widget::column().spacing(4);
+
+    for path in config.get_art_paths(list) {
+        folders = folders.push(
+            cosmic::widget::row()
+                .spacing(space_m)
+                .push(
+                    cosmic::widget::text::caption(path.display().to_string())
+                        .width(cosmic::iced::Length::Fill),
+                )
+                .push(
+                    cosmic::widget::button::icon(cosmic::widget::icon::from_name(
+                        "edit-delete-symbolic",
+                    ))
+                    .on_press(Message::RemoveArtPath(list, path.clone())),
+                )
+                .align_y(cosmic::iced::Alignment::Center),
+        );
+    }
+
+    let input = match list {
+        ArtPathList::Allowed => &app.art_allowed_input,
+        ArtPathList::Denied => &app.art_denied_input,
+    };
+    let add_button = cosmic::widget::button::text("Add");
+    let add_button = if input.trim().is_empty() {
+        add_button
+    } else {
+        add_button.on_press(Message::AddArtPath(list))
+    };
+
+    folders
+        .push(
+            cosmic::widget::row()
+                .spacing(space_m)
+                .push(
+                    cosmic::widget::text_input("/path/to/folder", input.as_str())
+                        .on_input(move |text| Message::ArtPathInput(list, text))
+                        .width(cosmic::iced::Length::Fill),
+                )
+                .push(add_button)
+                .align_y(cosmic::iced::Alignment::Center),
+        )
+        .into()
+}
+
+/// Renders the panel gesture binding controls of the Settings tab.
+///
+/// Each click gesture and scroll axis gets a dropdown of actions, followed by
+/// sliders for the volume and seek step sizes used by those actions.
+fn view_panel_bindings(config: &ConfigManager, space_m: f32) -> Element<'static, Message> {
+    let bindings = config.get_panel_bindings();
+
+    let click_row = |label: &'static str, gesture: ClickGesture| {
+        cosmic::widget::row()
+            .spacing(space_m)
+            .push(cosmic::widget::text::body(label).width(cosmic::iced::Length::Fill))
+            .push(cosmic::widget::dropdown(
+                &PanelAction::LABELS,
+                Some(bindings.click(gesture).index()),
+                move |index| Message::SetClickBinding(gesture, PanelAction::ALL[index]),
+            ))
+            .align_y(cosmic::iced::Alignment::Center)
+    };
+
+    let scroll_row = |label: &'static str, axis: ScrollAxis| {
+        cosmic::widget::row()
+            .spacing(space_m)
+            .push(cosmic::widget::text::body(label).width(cosmic::iced::Length::Fill))
+            .push(cosmic::widget::dropdown(
+                &ScrollAction::LABELS,
+                Some(bindings.scroll(axis).index()),
+                move |index| Message::SetScrollBinding(axis, ScrollAction::ALL[index]),
+            ))
+            .align_y(cosmic::iced::Alignment::Center)
+    };
+
+    let volume_step_row = cosmic::widget::row()
+        .spacing(space_m)
+        .push(cosmic::widget::text::body("Volume step").width(cosmic::iced::Length::Fill))
+        .push(
+            cosmic::widget::slider(0.01..=0.25, bindings.volume_step, Message::SetVolumeStep)
+                .step(0.01)
+                .width(cosmic::iced::Length::Fixed(120.0)),
+        )
+        .push(cosmic::widget::text::caption(format!(
+            "{:.0}%",
+            bindings.volume_step * 100.0
+        )))
+        .align_y(cosmic::iced::Alignment::Center);
+
+    let seek_step_row = cosmic::widget::row()
+        .spacing(space_m)
+        .push(cosmic::widget::text::body("Seek step").width(cosmic::iced::Length::Fill))
+        .push(
+            cosmic::widget::slider(1..=120, bindings.seek_step_secs, Message::SetSeekStep)
+                .width(cosmic::iced::Length::Fixed(120.0)),
+        )
+        .push(cosmic::widget::text::caption(format!(
+            "{}s",
+            bindings.seek_step_secs
+        )))
+        .align_y(cosmic::iced::Alignment::Center);
+
+    cosmic::widget::column()
+        .spacing(space_m)
+        .push(cosmic::widget::text::title4("Panel Icon Gestures"))
+        .push(click_row("Left click", ClickGesture::Left))
+        .push(click_row("Middle click", ClickGesture::Middle))
+        .push(click_row("Right click", ClickGesture::Right))
+        .push(click_row("Double click", ClickGesture::Double))
+        .push(scroll_row("Scroll", ScrollAxis::Vertical))
+        .push(scroll_row("Horizontal scroll", ScrollAxis::Horizontal))
+        .push(volume_step_row)
+        .push(seek_step_row)
+        .into()
+}
+
+/// Renders the volume slider settings of the Settings tab.
+fn view_volume_settings(
+    config: &ConfigManager,
+    players: &[DiscoveredPlayer],
+    space_m: f32,
+) -> Element<'static, Message> {
+    let curve_row = cosmic::widget::row()
+        .spacing(space_m)
+        .push(cosmic::widget::text::body("Slider curve").width(cosmic::iced::Length::Fill))
+        .push(cosmic::widget::dropdown(
+            &VolumeCurve::LABELS,
+            Some(config.get_volume_curve().index()),
+            |index| Message::SetVolumeCurve(VolumeCurve::ALL[index]),
+        ))
+        .align_y(cosmic::iced::Alignment::Center);
+
+    let mut column = cosmic::widget::column()
+        .spacing(space_m)
+        .push(cosmic::widget::text::title4("Volume"))
+        .push(curve_row)
+        .push(
+            cosmic::widget::checkbox(
+                "Allow boosting audio streams up to 150%",
+                config.get_volume_boost(),
+            )
+            .on_toggle(Message::ToggleVolumeBoost),
+        );
+
+    let quiet_hours = config.get_quiet_hours();
+    column = column
+        .push(optional_volume_row(
+            "Volume cap for every player".to_string(),
+            config.get_volume_cap(),
+            0.8,
+            Message::SetVolumeCap,
+            space_m,
+        ))
+        .push(
+            cosmic::widget::checkbox("Quiet hours", quiet_hours.enabled).on_toggle(
+                move |enabled| {
+                    Message::SetQuietHours(QuietHours {
+                        enabled,
+                        ..quiet_hours
+                    })
+                },
+            ),
+        );
+    if quiet_hours.enabled {
+        column = column.push(view_quiet_hours(quiet_hours, space_m));
+    }
+
+    if !players.is_empty() {
+        column = column.push(cosmic::widget::text::caption(
+            "Players start at their last volume unless a start volume is set:",
+        ));
+    }
+    for player in players {
+        let identity = player.identity.clone();
+        let cap_identity = player.identity.clone();
+        column = column
+            .push(cosmic::widget::text::body(player.identity.clone()))
+            .push(optional_volume_row(
+                "Start volume".to_string(),
+                config.get_start_volume(&player.identity),
+                config.get_player_volume(&player.identity).unwrap_or(0.5),
+                move |volume| Message::SetStartVolume(identity.clone(), volume),
+                space_m,
+            ))
+            .push(optional_volume_row(
+                "Volume cap".to_string(),
+                config.get_player_volume_cap(&player.identity),
+                0.8,
+                move |cap| Message::SetPlayerVolumeCap(cap_identity.clone(), cap),
+                space_m,
+            ));
+    }
+
+    column.into()
+}
+
+/// Visualizer toggle, bar count, smoothing and frame rate cap.
+fn view_spectrum_settings(config: &ConfigManager, space_m: f32) -> Element<'static, Message> {
+    let spectrum = config.get_spectrum();
+    let mut column = cosmic::widget::column()
+        .spacing(space_m)
+        .push(cosmic::widget::text::title4("Visualizer"))
+        .push(
+            cosmic::widget::checkbox("Show a spectrum under the album art", spectrum.enabled)
+                .on_toggle(move |enabled| {
+                    Message::SetSpectrum(SpectrumSettings {
+                        enabled,
+                        ..spectrum
+                    })
+                }),
+        );
+    if !spectrum.enabled {
+        return column.into();
+    }
+
+    let slider_row = |label: &'static str, slider: Element<'static, Message>, value: String| {
+        cosmic::widget::row()
+            .spacing(space_m)
+            .push(cosmic::widget::text::body(label).width(cosmic::iced::Length::Fill))
+            .push(slider)
+            .push(cosmic::widget::text::caption(value))
+            .align_y(cosmic::iced::Alignment::Center)
+    };
+    column = column
+        .push(slider_row(
+            "Bars",
+            cosmic::widget::slider(8..=64, spectrum.bar_count, move |bar_count| {
+                Message::SetSpectrum(SpectrumSettings {
+                    bar_count,
+                    ..spectrum
+                })
+            })
+            .width(cosmic::iced::Length::Fixed(120.0))
+            .into(),
+            spectrum.bar_count.to_string(),
+        ))
+        .push(slider_row(
+            "Smoothing",
+            cosmic::widget::slider(0.0..=0.95, spectrum.smoothing, move |smoothing| {
+                Message::SetSpectrum(SpectrumSettings {
+                    smoothing,
+                    ..spectrum
+                })
+            })
+            .step(0.05)
+            .width(cosmic::iced::Length::Fixed(120.0))
+            .into(),
+            format!("{:.0}%", spectrum.smoothing * 100.0),
+        ))
+        .push(slider_row(
+            "Frame rate",
+            cosmic::widget::slider(10..=60, spectrum.max_fps, move |max_fps| {
+                Message::SetSpectrum(SpectrumSettings {
+                    max_fps,
+                    ..spectrum
+                })
+            })
+            .width(cosmic::iced::Length::Fixed(120.0))
+            .into(),
+            format!("{} fps", spectrum.max_fps),
+        ));
+
+    column.into()
+}
+
+/// Half-hour steps of the quiet hours start and end dropdowns.
+static QUIET_HOURS_LABELS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    (0..48)
+        .map(|step| format!("{:02}:{:02}", step / 2, step % 2 * 30))
+        .collect()
+});
+
+/// Start, end and cap of the quiet hours.
+fn view_quiet_hours(quiet_hours: QuietHours, space_m: f32) -> Element<'static, Message> {
+    let window_row = cosmic::widget::row()
+        .spacing(space_m)
+        .push(cosmic::widget::text::body("From").width(cosmic::iced::Length::Fill))
+        .push(cosmic::widget::dropdown(
+            QUIET_HOURS_LABELS.as_slice(),
+            Some((quiet_hours.start_minutes / 30) as usize),
+            move |step| {
+                Message::SetQuietHours(QuietHours {
+                    start_minutes: step as u32 * 30,
+                    ..quiet_hours
+                })
+            },
+        ))
+        .push(cosmic::widget::text::body("until"))
+        .push(cosmic::widget::dropdown(
+            QUIET_HOURS_LABELS.as_slice(),
+            Some((quiet_hours.end_minutes / 30) as usize),
+            move |step| {
+                Message::SetQuietHours(QuietHours {
+                    end_minutes: step as u32 * 30,
+                    ..quiet_hours
+                })
+            },
+        ))
+        .align_y(cosmic::iced::Alignment::Center);
+
+    cosmic::widget::column()
+        .spacing(space_m)
+        .push(window_row)
+        .push(volume_setting_row(
+            "Volume cap".to_string(),
+            quiet_hours.cap,
+            move |cap| Message::SetQuietHours(QuietHours { cap, ..quiet_hours }),
+            space_m,
+        ))
+        .into()
+}
+
+/// An optional volume setting: a checkbox, and a slider while it is set.
+/// Checking the box starts the slider at `initial`.
+fn optional_volume_row(
+    label: String,
+    value: Option<f64>,
+    initial: f64,
+    on_change: impl Fn(Option<f64>) -> Message + Clone + 'static,
+    space_m: f32,
+) -> Element<'static, Message> {
+    let Some(volume) = value else {
+        return cosmic::widget::checkbox(label, false)
+            .on_toggle(move |enabled| on_change(enabled.then_some(initial)))
+            .into();
+    };
+
+    let on_toggle = on_change.clone();
+    cosmic::widget::row()
+        .spacing(space_m)
+        .push(
+            cosmic::widget::checkbox("", true)
+                .on_toggle(move |enabled| on_toggle(enabled.then_some(volume))),
+        )
+        .push(volume_setting_row(
+            label,
+            volume,
+            move |volume| on_change(Some(volume)),
+            space_m,
+        ))
+        .align_y(cosmic::iced::Alignment::Center)
+        .into()
+}
+
+/// A labelled 0–100% slider with its value.
+fn volume_setting_row(
+    label: String,
+    volume: f64,
+    on_change: impl Fn(f64) -> Message + 'static,
+    space_m: f32,
+) -> Element<'static, Message> {
+    cosmic::widget::row()
+        .spacing(space_m)
+        .push(cosmic::widget::text::body(label).width(cosmic::iced::Length::Fill))
+        .push(
+            cosmic::widget::slider(0.0..=1.0, volume, on_change)
+                .step(0.01)
+                .width(cosmic::iced::Length::Fixed(120.0)),
+        )
+        .push(cosmic::widget::text::caption(format!(
+            "{:.0}%",
+            volume * 100.0
+        )))
+        .align_y(cosmic::iced::Alignment::Center)
+        .into()
+}
+
 /// Renders all active media players in multi-player mode.
 ///
-/// Displays a scrollable list of compact player cards, each showing:
+/// Displays a list of compact player cards, each showing:
 /// - Optional album artwork (48x48)
 /// - Player name, track, and artist
 /// - Playback controls (previous, play/pause, next)
@@ -408,26 +1531,10 @@ fn view_settings_tab(app: &CosmicAppletMusic, _space_s: f32, space_m: f32) -> El
 /// * `space_m` - Medium spacing value from theme
 ///
 /// # Returns
-/// A scrollable Element containing player cards
+/// An Element containing player cards, scrolled by the Controls tab
 fn view_all_players(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Element<'_, Message> {
-    let hide_inactive = app
-        .config_manager
-        .as_ref()
-        .is_some_and(ConfigManager::get_hide_inactive_players);
-
     // Filter players based on hide_inactive setting
-    // Only hide Stopped players, keep Playing and Paused visible
-    let players_to_show: Vec<_> = app
-        .all_players_info
-        .iter()
-        .filter(|player| {
-            if hide_inactive {
-                player.status != PlaybackStatus::Stopped
-            } else {
-                true
-            }
-        })
-        .collect();
+    let players_to_show = app.visible_players();
 
     if players_to_show.is_empty() {
         return cosmic::widget::container(
@@ -449,13 +1556,13 @@ fn view_all_players(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Elem
 
     let mut players_column = cosmic::widget::column().spacing(space_s);
 
-    for player in players_to_show {
-        players_column = players_column.push(view_player_card(app, player, space_s, space_m));
+    for (index, player) in players_to_show.into_iter().enumerate() {
+        let focused = KeyboardFocus::button_on(app.keyboard_focus, index);
+        players_column =
+            players_column.push(view_player_card(app, player, focused, space_s, space_m));
     }
 
-    cosmic::widget::scrollable(players_column)
-        .height(cosmic::iced::Length::Fixed(450.0))
-        .into()
+    players_column.into()
 }
 
 /// Renders a compact player card for multi-player mode.
@@ -471,6 +1578,7 @@ fn view_all_players(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Elem
 /// # Arguments
 /// * `app` - Application state for accessing album art cache
 /// * `player` - Player information to display
+/// * `focused` - The card's transport button with keyboard focus, if any
 /// * `space_s` - Small spacing value from theme
 /// * `_space_m` - Medium spacing value (unused)
 ///
@@ -480,6 +1588,7 @@ fn view_all_players(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Elem
 fn view_player_card<'a>(
     app: &'a CosmicAppletMusic,
     player: &'a crate::music::PlayerInfo,
+    focused: Option<TransportButton>,
     space_s: f32,
     _space_m: f32,
 ) -> Element<'a, Message> {
@@ -504,7 +1613,12 @@ fn view_player_card<'a>(
     // Compact album art (48x48) - only show if available
     let mut info_row = cosmic::widget::row().spacing(space_s);
 
-    if let Some(handle) = app.player_album_arts.get(&player.bus_name) {
+    let art_state = player
+        .art_url
+        .as_deref()
+        .map(|url| app.art_store.state(url, COMPACT_ART_SIZE));
+
+    if let Some(ArtState::Loaded(handle)) = art_state {
         // Show compact album art
         let compact_art = cosmic::widget::container(
             cosmic::widget::image(handle.clone())
@@ -512,25 +1626,41 @@ fn view_player_card<'a>(
                 .height(cosmic::iced::Length::Fill)
                 .content_fit(cosmic::iced::ContentFit::Contain),
         )
-        .width(cosmic::iced::Length::Fixed(48.0))
-        .height(cosmic::iced::Length::Fixed(48.0))
+        .width(cosmic::iced::Length::Fixed(f32::from(COMPACT_ART_SIZE)))
+        .height(cosmic::iced::Length::Fixed(f32::from(COMPACT_ART_SIZE)))
         .align_x(cosmic::iced::alignment::Horizontal::Center)
         .align_y(cosmic::iced::alignment::Vertical::Center)
         .class(cosmic::theme::Container::Card);
 
         info_row = info_row.push(compact_art);
-    } else if player.art_url.is_some() {
+    } else if matches!(art_state, Some(ArtState::Loading)) {
         // Show loading placeholder
         let loading_art = cosmic::widget::container(
             cosmic::widget::icon::from_name("image-loading-symbolic").size(24),
         )
-        .width(cosmic::iced::Length::Fixed(48.0))
-        .height(cosmic::iced::Length::Fixed(48.0))
+        .width(cosmic::iced::Length::Fixed(f32::from(COMPACT_ART_SIZE)))
+        .height(cosmic::iced::Length::Fixed(f32::from(COMPACT_ART_SIZE)))
         .align_x(cosmic::iced::alignment::Horizontal::Center)
         .align_y(cosmic::iced::alignment::Vertical::Center)
         .class(cosmic::theme::Container::Card);
 
         info_row = info_row.push(loading_art);
+    } else if let Some(ArtState::Failed(Some(reason))) = art_state {
+        // Art blocked by the folder settings
+        let blocked_art = cosmic::widget::tooltip(
+            cosmic::widget::container(
+                cosmic::widget::icon::from_name("dialog-warning-symbolic").size(24),
+            )
+            .width(cosmic::iced::Length::Fixed(f32::from(COMPACT_ART_SIZE)))
+            .height(cosmic::iced::Length::Fixed(f32::from(COMPACT_ART_SIZE)))
+            .align_x(cosmic::iced::alignment::Horizontal::Center)
+            .align_y(cosmic::iced::alignment::Vertical::Center)
+            .class(cosmic::theme::Container::Card),
+            cosmic::widget::text::caption(format!("Album art blocked: {reason}")),
+            cosmic::widget::tooltip::Position::Bottom,
+        );
+
+        info_row = info_row.push(blocked_art);
     }
 
     // Status indicator emoji
@@ -572,6 +1702,7 @@ fn view_player_card<'a>(
                 cosmic::widget::icon::from_name("media-skip-backward-symbolic").size(16),
             )
             .padding(4)
+            .selected(focused == Some(TransportButton::Previous))
             .on_press({
                 let bus_name = bus_name.clone();
                 Message::PreviousPlayer(bus_name)
@@ -580,6 +1711,7 @@ fn view_player_card<'a>(
         .push(
             cosmic::widget::button::icon(cosmic::widget::icon::from_name(status_icon).size(16))
                 .padding(4)
+                .selected(focused == Some(TransportButton::PlayPause))
                 .on_press({
                     let bus_name = bus_name.clone();
                     Message::PlayPausePlayer(bus_name)
@@ -590,6 +1722,7 @@ fn view_player_card<'a>(
                 cosmic::widget::icon::from_name("media-skip-forward-symbolic").size(16),
             )
             .padding(4)
+            .selected(focused == Some(TransportButton::Next))
             .on_press({
                 let bus_name = bus_name.clone();
                 Message::NextPlayer(bus_name)
@@ -611,27 +1744,55 @@ fn view_player_card<'a>(
         .spacing(space_s / 2.0)
         .push(controls_row);
 
+    let palette = app.accent_palette(player);
+
     if player.can_control_volume {
+        let player_slider = volume_slider(
+            app,
+            player.volume,
+            app.max_volume(player.volume_source == VolumeSource::Stream),
+            palette,
+            {
+                let bus_name = bus_name.clone();
+                move |v| Message::VolumeChangedPlayer(bus_name.clone(), v)
+            },
+        );
         let volume_row = cosmic::widget::row()
             .spacing(space_s / 2.0)
-            .push(cosmic::widget::icon::from_name("audio-volume-low-symbolic").size(12))
             .push(
-                cosmic::widget::slider(0.0..=1.0, player.volume, {
-                    let bus_name = bus_name.clone();
-                    move |v| Message::VolumeChangedPlayer(bus_name.clone(), v)
-                })
-                .step(0.01)
-                .width(cosmic::iced::Length::Fill),
+                cosmic::widget::button::icon(
+                    cosmic::widget::icon::from_name(volume_icon(player)).size(12),
+                )
+                .padding(2)
+                .on_press(Message::ToggleMutePlayer(bus_name.clone())),
             )
+            .push(player_slider)
             .push(cosmic::widget::icon::from_name("audio-volume-high-symbolic").size(12))
             .align_y(cosmic::iced::Alignment::Center);
 
         card_content = card_content.push(volume_row);
+
+        if let Some(meter) = view_level_meter(app, player, 3.0) {
+            card_content = card_content.push(meter);
+        }
+        if let Some(cap) = view_volume_cap(app, player, 10) {
+            card_content = card_content.push(cap);
+        }
+        if let Some(streams) = view_streams(app, player, 10) {
+            card_content = card_content.push(streams);
+        }
+        if let Some(output) = view_output(app, player, 12) {
+            card_content = card_content.push(output);
+        }
     }
+    card_content = card_content.push(view_volume_source(app, player, 10));
 
     cosmic::widget::container(card_content)
         .padding([space_s, space_s * 1.5])
-        .class(cosmic::theme::Container::Card)
+        .class(match palette {
+            Some(palette) => cosmic::theme::Container::custom(accent::card_style(palette)),
+            None => cosmic::theme::Container::Card,
+        })
         .width(cosmic::iced::Length::Fill)
         .into()
 }
@@ -0,0 +1,356 @@
+//! Album art loading for local files, `data:` URIs and remote URLs.
+//!
+//! Remote art is stored in an on-disk cache (see [`cache`]) so that it shows
+//! up instantly after the first download and survives restarts. A `file://`
+//! URL pointing at an audio file yields its embedded cover (see [`embedded`]),
+//! and internal `musicbrainz:` URLs are resolved online (see [`coverart`]).
+//! Local reads are restricted to the folders allowed in [`paths`].
+//! Whatever the source, the bytes are decoded off the UI thread (see [`decode`]).
+
+pub mod cache;
+pub mod coverart;
+pub mod decode;
+mod embedded;
+pub mod fetch;
+pub mod palette;
+pub mod paths;
+pub mod store;
+
+use base64::Engine;
+use cache::DiskCache;
+use coverart::CoverArtLookup;
+use decode::ArtImage;
+use fetch::FetchPolicy;
+use paths::PathPolicy;
+use std::path::PathBuf;
+
+/// Maximum image size to prevent memory exhaustion attacks
+const MAX_IMAGE_SIZE: usize = 10 * 1024 * 1024; // 10MB
+
+/// Settings that shape how art is loaded, snapshotted from the config for
+/// each fetch.
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    pub cache: Option<DiskCache>,
+    /// Online lookup services; `None` when online lookup is disabled
+    pub cover_lookup: Option<CoverArtLookup>,
+    /// Logical sizes the art is drawn at
+    pub sizes: Vec<u16>,
+    /// Output scale factor, to pre-scale art for HiDPI
+    pub scale_factor: f32,
+    pub policy: FetchPolicy,
+    pub paths: PathPolicy,
+}
+
+/// Why art could not be shown.
+#[derive(Debug, Clone)]
+pub enum ArtError {
+    /// Blocked by the user's settings; the reason is shown in the UI
+    Rejected(String),
+    /// Missing or not a valid image
+    Unavailable,
+    /// Network error or server hiccup; worth retrying later
+    Transient,
+}
+
+pub async fn load_image_from_url(url: &str, options: LoadOptions) -> Result<ArtImage, ArtError> {
+    let sizes = options.sizes.clone();
+    let scale_factor = options.scale_factor;
+    let bytes = load_bytes(url, options).await?;
+
+    tokio::task::spawn_blocking(move || decode::decode_art(&bytes, &sizes, scale_factor))
+        .await
+        .ok()
+        .flatten()
+        .ok_or(ArtError::Unavailable)
+}
+
+async fn load_bytes(url: &str, options: LoadOptions) -> Result<Vec<u8>, ArtError> {
+    // Handle file:// URLs (common for local album art from players like VLC, Lollypop)
+    if url.starts_with("file://") {
+        let Some(path) = file_url_to_path(url) else {
+            eprintln!("Invalid album art file URL");
+            return Err(ArtError::Unavailable);
+        };
+        load_local_file(path, &options.paths).await
+    }
+    // Handle inline data:image/...;base64 URIs
+    else if url.starts_with("data:") {
+        decode_data_uri(url).ok_or(ArtError::Unavailable)
+    }
+    // Handle HTTP/HTTPS URLs
+    else if url.starts_with("http://") || url.starts_with("https://") {
+        fetch_remote(url, options.cache, &options.policy).await
+    }
+    // Handle internal musicbrainz: lookup URLs
+    else if url.starts_with(coverart::SCHEME) {
+        let Some(lookup) = options.cover_lookup else {
+            return Err(ArtError::Unavailable);
+        };
+        lookup_cover(url, &lookup, options.cache, &options.policy).await
+    } else {
+        eprintln!("Unsupported album art URL scheme");
+        Err(ArtError::Unavailable)
+    }
+}
+
+/// Convert a `file://` URL to a path, decoding percent-escapes such as `%20`.
+fn file_url_to_path(url: &str) -> Option<PathBuf> {
+    reqwest::Url::parse(url).ok()?.to_file_path().ok()
+}
+
+/// Whether a track URL is a local audio file, whose embedded cover can stand
+/// in for missing art. Other files, such as videos, are never read for art.
+pub fn is_audio_track_url(url: &str) -> bool {
+    file_url_to_path(url).is_some_and(|path| embedded::is_audio_file(&path))
+}
+
+async fn load_local_file(raw_path: PathBuf, policy: &PathPolicy) -> Result<Vec<u8>, ArtError> {
+    let canonical = canonicalize_allowed(raw_path, policy).await?;
+
+    // Audio file (track URL used as art fallback): embedded picture first,
+    // then a cover image from the same directory
+    let canonical = if embedded::is_audio_file(&canonical) {
+        let audio_path = canonical.clone();
+        let found = tokio::task::spawn_blocking(move || embedded::find_track_art(&audio_path))
+            .await
+            .ok()
+            .flatten()
+            .ok_or(ArtError::Unavailable)?;
+
+        match found {
+            embedded::TrackArt::Embedded(bytes) => {
+                if bytes.len() > MAX_IMAGE_SIZE {
+                    eprintln!("Embedded album art too large: {} bytes", bytes.len());
+                    return Err(ArtError::Unavailable);
+                }
+                return Ok(bytes);
+            }
+            // The cover may be a symlink out of the allowed folders
+            embedded::TrackArt::CoverFile(cover) => canonicalize_allowed(cover, policy).await?,
+        }
+    } else {
+        canonical
+    };
+
+    // Check the size first so a huge file is never read into memory
+    match tokio::fs::metadata(&canonical).await {
+        Ok(metadata) if metadata.len() > MAX_IMAGE_SIZE as u64 => {
+            eprintln!("Album art file too large: {} bytes", metadata.len());
+            return Err(ArtError::Unavailable);
+        }
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Failed to load album art file: {e}");
+            return Err(ArtError::Unavailable);
+        }
+    }
+
+    match tokio::fs::read(&canonical).await {
+        Ok(bytes) => {
+            if bytes.len() > MAX_IMAGE_SIZE {
+                eprintln!("Album art file too large: {} bytes", bytes.len());
+                return Err(ArtError::Unavailable);
+            }
+            Ok(bytes)
+        }
+        Err(e) => {
+            eprintln!("Failed to load album art file: {e}");
+            Err(ArtError::Unavailable)
+        }
+    }
+}
+
+/// Resolve symlinks and ".." traversal, then check the result against the
+/// path policy to prevent arbitrary local file disclosure via malicious MPRIS
+/// metadata.
+async fn canonicalize_allowed(path: PathBuf, policy: &PathPolicy) -> Result<PathBuf, ArtError> {
+    let canonical = match tokio::fs::canonicalize(path).await {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Album art file not found: {e}");
+            return Err(ArtError::Unavailable);
+        }
+    };
+
+    if let Err(reason) = policy.check(&canonical) {
+        eprintln!("Album art path rejected: {reason}");
+        return Err(ArtError::Rejected(reason));
+    }
+    Ok(canonical)
+}
+
+/// Decode a `data:image/<type>;base64,<payload>` URI.
+fn decode_data_uri(url: &str) -> Option<Vec<u8>> {
+    let (header, payload) = url.strip_prefix("data:")?.split_once(',')?;
+    let Some(media_type) = header.strip_suffix(";base64") else {
+        eprintln!("Unsupported album art data URI encoding");
+        return None;
+    };
+    if !media_type.starts_with("image/") {
+        eprintln!("Album art data URI is not an image");
+        return None;
+    }
+
+    // Base64 expands by 4/3; reject oversized payloads before decoding
+    if payload.len() / 4 * 3 > MAX_IMAGE_SIZE {
+        eprintln!("Album art data URI too large: {} bytes", payload.len());
+        return None;
+    }
+
+    match base64::engine::general_purpose::STANDARD.decode(payload.trim()) {
+        Ok(bytes) => Some(bytes),
+        Err(e) => {
+            eprintln!("Failed to decode album art data URI: {e}");
+            None
+        }
+    }
+}
+
+/// Download remote art, serving it from the disk cache when possible.
+///
+/// Fresh cache entries are returned without touching the network. Stale ones
+/// are revalidated with `If-None-Match`/`If-Modified-Since`, and still served
+/// if the server can't be reached or offline mode is on.
+async fn fetch_remote(
+    url: &str,
+    cache: Option<DiskCache>,
+    policy: &FetchPolicy,
+) -> Result<Vec<u8>, ArtError> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| ArtError::Unavailable)?;
+    if let Err(reason) = policy.check_url(&parsed) {
+        if !policy.offline {
+            eprintln!("Album art URL rejected: {reason}");
+            return Err(ArtError::Unavailable);
+        }
+    }
+
+    let cached = match cache.clone() {
+        Some(cache) => {
+            let url = url.to_string();
+            tokio::task::spawn_blocking(move || cache.get(&url))
+                .await
+                .ok()
+                .flatten()
+        }
+        None => None,
+    };
+
+    if let Some(ref entry) = cached {
+        if entry.meta.is_fresh() || policy.offline {
+            return Ok(entry.bytes.clone());
+        }
+    }
+    if policy.offline {
+        return Err(ArtError::Unavailable);
+    }
+
+    let mut request = fetch::client(policy).get(parsed);
+    if let Some(ref entry) = cached {
+        if let Some(ref etag) = entry.meta.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(ref last_modified) = entry.meta.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("Failed to fetch album art: {e}");
+            // Offline: a stale copy is better than nothing
+            return cached
+                .map(|entry| entry.bytes)
+                .ok_or_else(|| fetch::request_error(&e));
+        }
+    };
+
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        if let (Some(cache), Some(entry)) = (cache, cached) {
+            let url = url.to_string();
+            let _ = tokio::task::spawn_blocking(move || cache.mark_validated(&url)).await;
+            return Ok(entry.bytes);
+        }
+        return Err(ArtError::Unavailable);
+    }
+    if !response.status().is_success() {
+        eprintln!("Album art server answered {}", response.status());
+        let error = fetch::status_error(response.status());
+        return cached.map(|entry| entry.bytes).ok_or(error);
+    }
+
+    let header = |name: reqwest::header::HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(ToString::to_string)
+    };
+    let etag = header(reqwest::header::ETAG);
+    let last_modified = header(reqwest::header::LAST_MODIFIED);
+
+    let bytes = fetch::read_image_body(response, MAX_IMAGE_SIZE).await?;
+
+    let Some(cache) = cache else {
+        return Ok(bytes);
+    };
+
+    let url = url.to_string();
+    let stored = tokio::task::spawn_blocking(move || {
+        let thumbnail = cache::normalize_thumbnail(&bytes).unwrap_or(bytes);
+        if let Err(e) = cache.put(&url, &thumbnail, etag, last_modified) {
+            eprintln!("Failed to store album art in cache: {e}");
+        }
+        thumbnail
+    })
+    .await;
+
+    stored.map_err(|_| ArtError::Unavailable)
+}
+
+/// Resolve an online cover lookup, using the disk cache for earlier results.
+///
+/// Releases don't change their cover, so cached lookups are never revalidated.
+async fn lookup_cover(
+    url: &str,
+    lookup: &CoverArtLookup,
+    cache: Option<DiskCache>,
+    policy: &FetchPolicy,
+) -> Result<Vec<u8>, ArtError> {
+    if let Some(cache) = cache.clone() {
+        let key = url.to_string();
+        let cached = tokio::task::spawn_blocking(move || cache.get(&key))
+            .await
+            .ok()
+            .flatten();
+        if let Some(entry) = cached {
+            return Ok(entry.bytes);
+        }
+    }
+
+    if policy.offline {
+        return Err(ArtError::Unavailable);
+    }
+    // The lookup services are configured by the user rather than taken from
+    // player metadata, so a mirror on the local network is fine
+    let policy = FetchPolicy {
+        allow_private_networks: true,
+        ..policy.clone()
+    };
+    let bytes = coverart::fetch_cover(&fetch::client(&policy), lookup, url, MAX_IMAGE_SIZE).await?;
+
+    let Some(cache) = cache else {
+        return Ok(bytes);
+    };
+    let url = url.to_string();
+    tokio::task::spawn_blocking(move || {
+        let thumbnail = cache::normalize_thumbnail(&bytes).unwrap_or(bytes);
+        if let Err(e) = cache.put(&url, &thumbnail, None, None) {
+            eprintln!("Failed to store album art in cache: {e}");
+        }
+        thumbnail
+    })
+    .await
+    .map_err(|_| ArtError::Unavailable)
+}
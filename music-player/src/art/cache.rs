@@ -0,0 +1,374 @@
+//! Size-bounded LRU disk cache for remote album art.
+//!
+//! Entries live under `$XDG_CACHE_HOME/com.github.MusicPlayer/album-art`, keyed
+//! by the SHA-256 of the art URL. Each entry is a normalized PNG thumbnail plus
+//! a small TOML sidecar holding the HTTP validators. Recency is tracked through
+//! the thumbnail's modification time, which is bumped on every hit.
+
+use super::decode;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a cached entry is trusted before it is revalidated with the server.
+const REVALIDATE_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Longest edge in pixels of the thumbnails stored on disk.
+const THUMBNAIL_SIZE: u32 = 256;
+
+const IMAGE_EXTENSION: &str = "png";
+const META_EXTENSION: &str = "toml";
+
+/// HTTP validators and bookkeeping stored next to each cached image.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheMeta {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Unix time (seconds) of the last download or successful revalidation
+    pub validated_at: u64,
+}
+
+impl CacheMeta {
+    pub fn is_fresh(&self) -> bool {
+        unix_now().saturating_sub(self.validated_at) < REVALIDATE_AFTER.as_secs()
+    }
+}
+
+pub struct CachedArt {
+    pub bytes: Vec<u8>,
+    pub meta: CacheMeta,
+}
+
+/// Handle to the album art cache directory.
+///
+/// All methods do blocking file I/O; call them from `spawn_blocking`.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl DiskCache {
+    /// Returns `None` when the platform has no cache directory.
+    pub fn new(max_bytes: u64) -> Option<Self> {
+        dirs::cache_dir().map(|dir| Self {
+            dir: dir.join("com.github.MusicPlayer").join("album-art"),
+            max_bytes,
+        })
+    }
+
+    fn key(url: &str) -> String {
+        format!("{:x}", Sha256::digest(url.as_bytes()))
+    }
+
+    fn entry_path(&self, url: &str, extension: &str) -> PathBuf {
+        self.dir.join(Self::key(url)).with_extension(extension)
+    }
+
+    pub fn get(&self, url: &str) -> Option<CachedArt> {
+        let image_path = self.entry_path(url, IMAGE_EXTENSION);
+        let meta: CacheMeta =
+            toml::from_str(&fs::read_to_string(self.entry_path(url, META_EXTENSION)).ok()?).ok()?;
+        // Guard against (astronomically unlikely) hash collisions
+        if meta.url != url {
+            return None;
+        }
+        let bytes = fs::read(&image_path).ok()?;
+
+        // Bump recency for LRU eviction
+        if let Ok(file) = fs::File::options().write(true).open(&image_path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+
+        Some(CachedArt { bytes, meta })
+    }
+
+    pub fn put(
+        &self,
+        url: &str,
+        bytes: &[u8],
+        etag: Option<String>,
+        last_modified: Option<String>,
+    ) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let meta = CacheMeta {
+            url: url.to_string(),
+            etag,
+            last_modified,
+            validated_at: unix_now(),
+        };
+        fs::write(self.entry_path(url, IMAGE_EXTENSION), bytes)?;
+        fs::write(
+            self.entry_path(url, META_EXTENSION),
+            toml::to_string(&meta)?,
+        )?;
+        self.evict()
+    }
+
+    /// Record a `304 Not Modified` answer so the entry is trusted again.
+    pub fn mark_validated(&self, url: &str) -> Result<()> {
+        let meta_path = self.entry_path(url, META_EXTENSION);
+        let mut meta: CacheMeta = toml::from_str(&fs::read_to_string(&meta_path)?)?;
+        meta.validated_at = unix_now();
+        fs::write(meta_path, toml::to_string(&meta)?)?;
+        Ok(())
+    }
+
+    /// Total bytes used by the cache directory.
+    pub fn usage(&self) -> u64 {
+        self.entries().iter().map(|(_, size, _)| size).sum()
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    /// All files in the cache directory as `(path, size, modified)`.
+    fn entries(&self) -> Vec<(PathBuf, u64, SystemTime)> {
+        let Ok(read_dir) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        read_dir
+            .flatten()
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                metadata.is_file().then(|| {
+                    (
+                        entry.path(),
+                        metadata.len(),
+                        metadata.modified().unwrap_or(UNIX_EPOCH),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Delete least recently used images (and their sidecars) until the cache
+    /// fits in `max_bytes`.
+    fn evict(&self) -> Result<()> {
+        let entries = self.entries();
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        if total <= self.max_bytes {
+            return Ok(());
+        }
+
+        let mut images: Vec<_> = entries
+            .into_iter()
+            .filter(|(path, _, _)| {
+                path.extension().and_then(|e| e.to_str()) == Some(IMAGE_EXTENSION)
+            })
+            .collect();
+        images.sort_by_key(|(_, _, modified)| *modified);
+
+        for (path, size, _) in images {
+            if total <= self.max_bytes {
+                break;
+            }
+            let meta_path = path.with_extension(META_EXTENSION);
+            let meta_size = fs::metadata(&meta_path).map_or(0, |m| m.len());
+            fs::remove_file(&path)?;
+            let _ = fs::remove_file(&meta_path);
+            total = total.saturating_sub(size + meta_size);
+        }
+        Ok(())
+    }
+}
+
+/// Decode an image under the same limits as [`decode::decode_art`] and
+/// re-encode it as a PNG no larger than `THUMBNAIL_SIZE`.
+///
+/// Returns `None` if the bytes are not a decodable image or are too large.
+pub fn normalize_thumbnail(bytes: &[u8]) -> Option<Vec<u8>> {
+    let image = match decode::decode_bounded(bytes) {
+        Ok(image) => image,
+        Err(e) => {
+            eprintln!("Failed to normalize album art: {e}");
+            return None;
+        }
+    };
+    let image = if image.width() > THUMBNAIL_SIZE || image.height() > THUMBNAIL_SIZE {
+        image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+    } else {
+        image
+    };
+    let mut encoded = Cursor::new(Vec::new());
+    image.write_to(&mut encoded, image::ImageFormat::Png).ok()?;
+    Some(encoded.into_inner())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A cache in a fresh directory under the system temp dir.
+    fn temp_cache(name: &str, max_bytes: u64) -> DiskCache {
+        let dir = std::env::temp_dir().join(format!(
+            "music-player-cache-test-{}-{name}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        DiskCache { dir, max_bytes }
+    }
+
+    fn write_sidecar(cache: &DiskCache, url: &str, sidecar: &str) {
+        fs::create_dir_all(&cache.dir).unwrap();
+        fs::write(cache.entry_path(url, IMAGE_EXTENSION), b"image").unwrap();
+        fs::write(cache.entry_path(url, META_EXTENSION), sidecar).unwrap();
+    }
+
+    fn age(cache: &DiskCache, url: &str, seconds: u64) {
+        let file = fs::File::options()
+            .write(true)
+            .open(cache.entry_path(url, IMAGE_EXTENSION))
+            .unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(seconds))
+            .unwrap();
+    }
+
+    #[test]
+    fn put_then_get_round_trips() {
+        let cache = temp_cache("round-trip", u64::MAX);
+        let url = "https://example.com/a.jpg";
+        assert!(cache.get(url).is_none());
+
+        cache
+            .put(url, b"image", Some("\"v1\"".to_string()), None)
+            .unwrap();
+        let entry = cache.get(url).unwrap();
+        assert_eq!(entry.bytes, b"image");
+        assert_eq!(entry.meta.url, url);
+        assert_eq!(entry.meta.etag.as_deref(), Some("\"v1\""));
+        assert!(entry.meta.last_modified.is_none());
+        assert!(entry.meta.is_fresh());
+
+        cache.clear().unwrap();
+        assert!(cache.get(url).is_none());
+        assert_eq!(cache.usage(), 0);
+    }
+
+    #[test]
+    fn sidecars_are_parsed_and_checked() {
+        let cache = temp_cache("sidecar", u64::MAX);
+        let url = "https://example.com/a.jpg";
+
+        write_sidecar(
+            &cache,
+            url,
+            "url = \"https://example.com/a.jpg\"\n\
+             etag = \"abc\"\n\
+             last_modified = \"Wed, 21 Oct 2015 07:28:00 GMT\"\n\
+             validated_at = 1700000000\n",
+        );
+        let meta = cache.get(url).unwrap().meta;
+        assert_eq!(meta.etag.as_deref(), Some("abc"));
+        assert_eq!(
+            meta.last_modified.as_deref(),
+            Some("Wed, 21 Oct 2015 07:28:00 GMT")
+        );
+        assert_eq!(meta.validated_at, 1_700_000_000);
+
+        // Validators are optional
+        write_sidecar(
+            &cache,
+            url,
+            "url = \"https://example.com/a.jpg\"\nvalidated_at = 0\n",
+        );
+        assert!(cache.get(url).unwrap().meta.etag.is_none());
+
+        // A sidecar for another URL (hash collision) is a miss
+        write_sidecar(
+            &cache,
+            url,
+            "url = \"https://example.com/b.jpg\"\nvalidated_at = 0\n",
+        );
+        assert!(cache.get(url).is_none());
+
+        // So is a corrupt one
+        write_sidecar(&cache, url, "not toml at all [");
+        assert!(cache.get(url).is_none());
+
+        cache.clear().unwrap();
+    }
+
+    #[test]
+    fn entries_are_revalidated_after_a_day() {
+        let meta = |age: u64| CacheMeta {
+            validated_at: unix_now() - age,
+            ..CacheMeta::default()
+        };
+        assert!(meta(0).is_fresh());
+        assert!(meta(REVALIDATE_AFTER.as_secs() - 60).is_fresh());
+        assert!(!meta(REVALIDATE_AFTER.as_secs()).is_fresh());
+        assert!(!meta(REVALIDATE_AFTER.as_secs() * 7).is_fresh());
+
+        // A 304 answer makes a stale entry fresh again
+        let cache = temp_cache("revalidate", u64::MAX);
+        let url = "https://example.com/a.jpg";
+        write_sidecar(
+            &cache,
+            url,
+            &format!(
+                "url = \"{url}\"\netag = \"abc\"\nvalidated_at = {}\n",
+                unix_now() - REVALIDATE_AFTER.as_secs() - 1
+            ),
+        );
+        assert!(!cache.get(url).unwrap().meta.is_fresh());
+        cache.mark_validated(url).unwrap();
+        let meta = cache.get(url).unwrap().meta;
+        assert!(meta.is_fresh());
+        assert_eq!(meta.etag.as_deref(), Some("abc"));
+
+        cache.clear().unwrap();
+    }
+
+    #[test]
+    fn least_recently_used_entries_are_evicted() {
+        let unbounded = temp_cache("evict", u64::MAX);
+        let [a, b, c] = [
+            "https://example.com/a.jpg",
+            "https://example.com/b.jpg",
+            "https://example.com/c.jpg",
+        ];
+        unbounded.put(a, &[0; 100], None, None).unwrap();
+        unbounded.put(b, &[0; 100], None, None).unwrap();
+        age(&unbounded, a, 200);
+        age(&unbounded, b, 100);
+
+        // Room for two entries but not three
+        let cache = DiskCache {
+            max_bytes: unbounded.usage() + 10,
+            ..unbounded
+        };
+        // Reading `a` makes it the most recently used entry
+        assert!(cache.get(a).is_some());
+        cache.put(c, &[0; 100], None, None).unwrap();
+
+        assert!(cache.get(a).is_some());
+        assert!(cache.get(b).is_none());
+        assert!(cache.get(c).is_some());
+        // The sidecar goes with its image
+        assert!(!cache.entry_path(b, META_EXTENSION).exists());
+        assert!(cache.usage() <= cache.max_bytes());
+
+        cache.clear().unwrap();
+    }
+}
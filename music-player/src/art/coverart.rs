@@ -0,0 +1,230 @@
+//! Optional online cover lookup through MusicBrainz and the Cover Art Archive.
+//!
+//! Used for players that publish artist and album but no art. Lookups are
+//! addressed with internal `musicbrainz:` URLs so they share the art store and
+//! disk cache with regular art URLs. Requests to MusicBrainz are spaced at
+//! least one second apart, as its usage policy requires.
+
+use super::fetch::{read_image_body, request_error, status_error};
+use super::ArtError;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+pub const SCHEME: &str = "musicbrainz:";
+
+/// Minimum delay between two MusicBrainz search requests.
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Base URLs of the lookup services, configurable to use a mirror.
+#[derive(Debug, Clone)]
+pub struct CoverArtLookup {
+    pub musicbrainz_url: String,
+    pub cover_art_archive_url: String,
+}
+
+#[derive(Deserialize)]
+struct ReleaseSearch {
+    #[serde(default)]
+    releases: Vec<Release>,
+}
+
+#[derive(Deserialize)]
+struct Release {
+    id: String,
+}
+
+/// Build the internal lookup URL for an artist/album pair.
+pub fn lookup_url(artist: &str, album: &str) -> Option<String> {
+    let mut url = reqwest::Url::parse(&format!("{SCHEME}release")).ok()?;
+    url.query_pairs_mut()
+        .append_pair("artist", artist)
+        .append_pair("album", album);
+    Some(url.to_string())
+}
+
+/// Extract the artist/album pair from a URL built by [`lookup_url`].
+fn parse_lookup_url(url: &str) -> Option<(String, String)> {
+    let url = reqwest::Url::parse(url).ok()?;
+    if url.scheme() != SCHEME.trim_end_matches(':') || url.path() != "release" {
+        return None;
+    }
+    let mut artist = None;
+    let mut album = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "artist" => artist = Some(value.into_owned()),
+            "album" => album = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+    Some((artist?, album?))
+}
+
+/// Escape Lucene special characters in a MusicBrainz search term.
+fn escape_query(term: &str) -> String {
+    term.chars()
+        .flat_map(|c| {
+            let escape = "+-&|!(){}[]^\"~*?:\\/".contains(c);
+            escape.then_some('\\').into_iter().chain(std::iter::once(c))
+        })
+        .collect()
+}
+
+/// Wait until the next MusicBrainz request is allowed.
+async fn rate_limit() {
+    static LAST_REQUEST: Mutex<Option<Instant>> = Mutex::const_new(None);
+
+    let mut last = LAST_REQUEST.lock().await;
+    if let Some(previous) = *last {
+        let elapsed = previous.elapsed();
+        if elapsed < MIN_REQUEST_INTERVAL {
+            tokio::time::sleep(MIN_REQUEST_INTERVAL - elapsed).await;
+        }
+    }
+    *last = Some(Instant::now());
+}
+
+/// Resolve a `musicbrainz:` lookup URL to front cover image bytes.
+pub async fn fetch_cover(
+    client: &reqwest::Client,
+    lookup: &CoverArtLookup,
+    url: &str,
+    max_size: usize,
+) -> Result<Vec<u8>, ArtError> {
+    let (artist, album) = parse_lookup_url(url).ok_or(ArtError::Unavailable)?;
+
+    rate_limit().await;
+    let query = format!(
+        "release:\"{}\" AND artist:\"{}\"",
+        escape_query(&album),
+        escape_query(&artist)
+    );
+    let search_url = format!(
+        "{}/ws/2/release/",
+        lookup.musicbrainz_url.trim_end_matches('/')
+    );
+    let response = client
+        .get(search_url)
+        .query(&[("query", query.as_str()), ("fmt", "json"), ("limit", "1")])
+        .send()
+        .await;
+    let response = match response {
+        Ok(response) if response.status().is_success() => response,
+        Ok(response) => {
+            eprintln!("MusicBrainz lookup failed: {}", response.status());
+            return Err(status_error(response.status()));
+        }
+        Err(e) => {
+            eprintln!("MusicBrainz lookup failed: {e}");
+            return Err(request_error(&e));
+        }
+    };
+    let body = response.text().await.map_err(|e| request_error(&e))?;
+    let release_id = match serde_json::from_str::<ReleaseSearch>(&body) {
+        Ok(search) => {
+            // No matching release is a definite answer, not an error
+            search
+                .releases
+                .into_iter()
+                .next()
+                .ok_or(ArtError::Unavailable)?
+                .id
+        }
+        Err(e) => {
+            eprintln!("Failed to parse MusicBrainz response: {e}");
+            return Err(ArtError::Unavailable);
+        }
+    };
+
+    let cover_url = format!(
+        "{}/release/{release_id}/front-250",
+        lookup.cover_art_archive_url.trim_end_matches('/')
+    );
+    match client.get(cover_url).send().await {
+        Ok(response) if response.status().is_success() => read_image_body(response, max_size).await,
+        Ok(response) => {
+            eprintln!("Cover Art Archive fetch failed: {}", response.status());
+            Err(status_error(response.status()))
+        }
+        Err(e) => {
+            eprintln!("Cover Art Archive fetch failed: {e}");
+            Err(request_error(&e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_urls_round_trip() {
+        for (artist, album) in [
+            ("Radiohead", "OK Computer"),
+            ("AC/DC", "Back in Black"),
+            ("Simon & Garfunkel", "Bridge over Troubled Water"),
+            ("Björk", "Homogenic"),
+            ("Sigur Rós", "( )"),
+            ("?", "#1 Record"),
+            ("A=B", "x+y%20z"),
+        ] {
+            let url = lookup_url(artist, album).unwrap();
+            assert!(url.starts_with("musicbrainz:release?"), "{url}");
+            assert_eq!(
+                parse_lookup_url(&url),
+                Some((artist.to_string(), album.to_string())),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn lookup_url_encodes_the_query() {
+        assert_eq!(
+            lookup_url("Simon & Garfunkel", "Bookends").as_deref(),
+            Some("musicbrainz:release?artist=Simon+%26+Garfunkel&album=Bookends")
+        );
+    }
+
+    #[test]
+    fn malformed_lookup_urls_are_rejected() {
+        for url in [
+            "",
+            "musicbrainz:release",
+            "musicbrainz:release?artist=Radiohead",
+            "musicbrainz:release?album=OK+Computer",
+            "musicbrainz:artist?artist=Radiohead&album=OK+Computer",
+            "https://musicbrainz.org/release?artist=Radiohead&album=OK+Computer",
+        ] {
+            assert_eq!(parse_lookup_url(url), None, "{url}");
+        }
+        // Unknown keys are ignored
+        assert_eq!(
+            parse_lookup_url("musicbrainz:release?artist=A&album=B&disc=2"),
+            Some(("A".to_string(), "B".to_string()))
+        );
+    }
+
+    #[test]
+    fn lucene_special_characters_are_escaped() {
+        for (term, escaped) in [
+            ("OK Computer", "OK Computer"),
+            ("AC/DC", "AC\\/DC"),
+            ("\"Heroes\"", "\\\"Heroes\\\""),
+            ("Help!", "Help\\!"),
+            (
+                "(What's the Story) Morning Glory?",
+                "\\(What's the Story\\) Morning Glory\\?",
+            ),
+            (
+                "+-&|!(){}[]^\"~*?:\\/",
+                "\\+\\-\\&\\|\\!\\(\\)\\{\\}\\[\\]\\^\\\"\\~\\*\\?\\:\\\\\\/",
+            ),
+            ("Björk", "Björk"),
+            ("", ""),
+        ] {
+            assert_eq!(escape_query(term), escaped, "{term}");
+        }
+    }
+}
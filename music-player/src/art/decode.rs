@@ -0,0 +1,176 @@
+//! Off-thread decoding and downscaling of album art.
+//!
+//! Art arrives at arbitrary sizes (a 4000×4000 PNG for an 80×80 box) and from
+//! untrusted sources. It is decoded here under dimension and allocation limits
+//! to defuse decompression bombs, then resized to the sizes the popup actually
+//! draws and handed to iced as pre-scaled RGBA. The accent palette is
+//! extracted in the same pass.
+
+use super::palette::{self, ArtPalette};
+use cosmic::iced::widget::image::Handle;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageError};
+use std::fmt;
+use std::io::Cursor;
+
+/// Largest accepted width or height in pixels.
+const MAX_DIMENSION: u32 = 8192;
+
+/// Largest accepted pixel count (width × height).
+const MAX_PIXELS: u64 = 16 * 1024 * 1024;
+
+/// Largest allocation the decoder may make.
+const MAX_ALLOC: u64 = 256 * 1024 * 1024;
+
+/// Why [`decode_bounded`] gave up on an image.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// Not a recognized image, or corrupt
+    Invalid(ImageError),
+    /// The header announces an image beyond the decode limits
+    TooLarge { width: u32, height: u32 },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(e) => e.fmt(f),
+            Self::TooLarge { width, height } => {
+                write!(f, "{width}x{height} exceeds decode limits")
+            }
+        }
+    }
+}
+
+/// Decode untrusted image bytes under the dimension, pixel and allocation
+/// limits. The header is checked first, so an oversized image is rejected
+/// before any pixel data is allocated.
+pub fn decode_bounded(bytes: &[u8]) -> Result<DynamicImage, DecodeError> {
+    let reader = || {
+        image::ImageReader::new(Cursor::new(bytes))
+            .with_guessed_format()
+            .map_err(|e| DecodeError::Invalid(e.into()))
+    };
+
+    let (width, height) = reader()?.into_dimensions().map_err(DecodeError::Invalid)?;
+    if width > MAX_DIMENSION
+        || height > MAX_DIMENSION
+        || u64::from(width) * u64::from(height) > MAX_PIXELS
+    {
+        return Err(DecodeError::TooLarge { width, height });
+    }
+
+    let mut limits = image::Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    limits.max_alloc = Some(MAX_ALLOC);
+
+    let mut decoder = reader()?;
+    decoder.limits(limits);
+    decoder.decode().map_err(DecodeError::Invalid)
+}
+
+/// Decoded art, pre-scaled to each requested logical size.
+#[derive(Debug, Clone)]
+pub struct ArtImage {
+    /// `(logical size, handle)` pairs, in the order they were requested
+    variants: Vec<(u16, Handle)>,
+    palette: Option<ArtPalette>,
+}
+
+impl ArtImage {
+    /// Handle for the variant closest to `size`.
+    pub fn handle(&self, size: u16) -> Option<&Handle> {
+        self.variants
+            .iter()
+            .min_by_key(|(variant, _)| variant.abs_diff(size))
+            .map(|(_, handle)| handle)
+    }
+
+    pub fn palette(&self) -> Option<ArtPalette> {
+        self.palette
+    }
+}
+
+/// Decode `bytes` and produce one variant per logical size, scaled by
+/// `scale_factor` for HiDPI outputs.
+///
+/// Returns `None` for undecodable or oversized images. Blocking; call from
+/// `spawn_blocking`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn decode_art(bytes: &[u8], sizes: &[u16], scale_factor: f32) -> Option<ArtImage> {
+    let image = match decode_bounded(bytes) {
+        Ok(image) => image,
+        Err(e) => {
+            eprintln!("Failed to decode album art: {e}");
+            return None;
+        }
+    };
+
+    let variants = sizes
+        .iter()
+        .map(|&size| {
+            let target = ((f32::from(size) * scale_factor).round() as u32).max(1);
+            // Only ever shrink; small art is left for iced to scale up
+            let scaled = if image.width() > target || image.height() > target {
+                image.resize(target, target, FilterType::Triangle)
+            } else {
+                image.clone()
+            };
+            let rgba = scaled.to_rgba8();
+            let handle = Handle::from_rgba(rgba.width(), rgba.height(), rgba.into_raw());
+            (size, handle)
+        })
+        .collect();
+
+    Some(ArtImage {
+        variants,
+        palette: palette::extract(&image),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A binary PPM header announcing `width`×`height` RGB pixels, followed by
+    /// `data`.
+    fn ppm(width: u32, height: u32, data: &[u8]) -> Vec<u8> {
+        let mut bytes = format!("P6\n{width} {height}\n255\n").into_bytes();
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn oversized_header_is_rejected_before_decoding() {
+        // No pixel data at all: decoding would fail differently
+        let result = decode_bounded(&ppm(20_000, 20_000, &[]));
+        assert!(matches!(
+            result,
+            Err(DecodeError::TooLarge {
+                width: 20_000,
+                height: 20_000
+            })
+        ));
+    }
+
+    #[test]
+    fn pixel_count_over_limit_is_rejected() {
+        let result = decode_bounded(&ppm(MAX_DIMENSION, MAX_DIMENSION, &[]));
+        assert!(matches!(result, Err(DecodeError::TooLarge { .. })));
+    }
+
+    #[test]
+    fn small_image_decodes() {
+        let image = decode_bounded(&ppm(2, 1, &[255, 0, 0, 0, 0, 255])).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 1));
+    }
+
+    #[test]
+    fn garbage_is_invalid() {
+        assert!(matches!(
+            decode_bounded(b"not an image"),
+            Err(DecodeError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,105 @@
+//! Cover art for local audio files, for players that publish a track URL but
+//! no `mpris:artUrl` (mpv, browsers playing local files, ...).
+//!
+//! The picture embedded in the file's tags (ID3, FLAC, MP4, Ogg/Vorbis
+//! comments) is preferred; otherwise a conventional cover image next to the
+//! file is used.
+
+use lofty::file::TaggedFileExt;
+use lofty::picture::PictureType;
+use std::path::{Path, PathBuf};
+
+/// File extensions treated as audio rather than image files.
+const AUDIO_EXTENSIONS: &[&str] = &[
+    "mp3", "flac", "m4a", "m4b", "mp4", "aac", "alac", "ogg", "oga", "opus", "wav", "wv", "ape",
+    "aiff", "aif",
+];
+
+/// Cover file stems searched next to the audio file, in order of preference.
+const COVER_STEMS: &[&str] = &["cover", "folder", "front", "album", "albumart"];
+
+const COVER_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp"];
+
+/// Art found for a local audio file.
+pub enum TrackArt {
+    /// Picture bytes taken from the file's tags
+    Embedded(Vec<u8>),
+    /// Cover image in the file's directory
+    CoverFile(PathBuf),
+}
+
+/// Look for the embedded picture, then for a cover file next to the track.
+///
+/// Blocking; call from `spawn_blocking`.
+pub fn find_track_art(path: &Path) -> Option<TrackArt> {
+    extract_embedded_picture(path)
+        .map(TrackArt::Embedded)
+        .or_else(|| find_directory_cover(path).map(TrackArt::CoverFile))
+}
+
+pub fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| {
+            AUDIO_EXTENSIONS
+                .iter()
+                .any(|audio| ext.eq_ignore_ascii_case(audio))
+        })
+}
+
+/// Extract the front cover (or the first picture) embedded in the file's tags.
+fn extract_embedded_picture(path: &Path) -> Option<Vec<u8>> {
+    let tagged_file = match lofty::read_from_path(path) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("Failed to read tags for embedded album art: {e}");
+            return None;
+        }
+    };
+
+    let pictures: Vec<_> = tagged_file
+        .tags()
+        .iter()
+        .flat_map(|tag| tag.pictures())
+        .collect();
+
+    pictures
+        .iter()
+        .find(|p| p.pic_type() == PictureType::CoverFront)
+        .or_else(|| pictures.first())
+        .map(|p| p.data().to_vec())
+}
+
+/// Find a conventional cover image (`cover.jpg`, `folder.png`, ...) in the
+/// directory of `path`. Names are matched case-insensitively.
+fn find_directory_cover(path: &Path) -> Option<PathBuf> {
+    let dir = path.parent()?;
+    let candidates: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.is_file())
+        .collect();
+
+    COVER_STEMS.iter().find_map(|stem| {
+        candidates
+            .iter()
+            .find(|candidate| {
+                let matches_stem = candidate
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .is_some_and(|s| s.eq_ignore_ascii_case(stem));
+                let matches_ext =
+                    candidate
+                        .extension()
+                        .and_then(|e| e.to_str())
+                        .is_some_and(|ext| {
+                            COVER_EXTENSIONS
+                                .iter()
+                                .any(|cover| ext.eq_ignore_ascii_case(cover))
+                        });
+                matches_stem && matches_ext
+            })
+            .cloned()
+    })
+}
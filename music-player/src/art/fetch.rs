@@ -0,0 +1,410 @@
+//! Policy-driven HTTP fetching of album art.
+//!
+//! Art URLs come from MPRIS metadata that any client on the session bus can
+//! set, so downloads are treated as untrusted: bodies are streamed with an
+//! early size cutoff, content types and magic bytes are checked, and loopback,
+//! private and link-local destinations are refused unless explicitly allowed.
+
+use super::ArtError;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Maximum number of redirects followed per request.
+const MAX_REDIRECTS: usize = 3;
+
+pub const DEFAULT_USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " ( https://github.com/olafkfreund/cosmic-applet-music-player )"
+);
+
+/// Network rules for art downloads, snapshotted from the config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchPolicy {
+    /// Never touch the network; only cached art is used
+    pub offline: bool,
+    /// Refuse plain `http://` URLs, including redirects to them
+    pub https_only: bool,
+    /// Allow loopback, private and link-local destinations
+    pub allow_private_networks: bool,
+    pub user_agent: String,
+}
+
+impl Default for FetchPolicy {
+    fn default() -> Self {
+        Self {
+            offline: false,
+            https_only: false,
+            allow_private_networks: false,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+        }
+    }
+}
+
+impl FetchPolicy {
+    /// Check a URL against the policy before connecting.
+    ///
+    /// Host names are checked again after DNS resolution by the client.
+    pub fn check_url(&self, url: &reqwest::Url) -> Result<(), String> {
+        if self.offline {
+            return Err("offline mode is enabled".to_string());
+        }
+        match url.scheme() {
+            "https" => {}
+            "http" if !self.https_only => {}
+            scheme => return Err(format!("scheme '{scheme}' is not allowed")),
+        }
+        if !self.allow_private_networks {
+            // IP literals bypass DNS, so they are checked here; IPv6 hosts
+            // come bracketed
+            let host = url.host_str().unwrap_or_default();
+            let literal = host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+                .ok();
+            if literal.is_some_and(|ip| !is_public_ip(ip)) {
+                return Err("destination is a local or private address".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a redirect target, `hops` redirects into the request.
+    pub fn check_redirect(&self, url: &reqwest::Url, hops: usize) -> Result<(), String> {
+        if hops >= MAX_REDIRECTS {
+            return Err("too many redirects".to_string());
+        }
+        self.check_url(url)
+    }
+}
+
+/// Returns true for globally routable addresses.
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            // "This network", 0.0.0.0/8
+            let this_network = a == 0;
+            // Carrier-grade NAT space, 100.64.0.0/10
+            let shared = a == 100 && (b & 0xc0) == 64;
+            // IETF protocol assignments, 192.0.0.0/24
+            let protocol = a == 192 && b == 0 && c == 0;
+            // Benchmarking, 198.18.0.0/15
+            let benchmarking = a == 198 && (b & 0xfe) == 18;
+            // Multicast 224.0.0.0/4 and reserved 240.0.0.0/4 (with broadcast)
+            let multicast_or_reserved = a >= 224;
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_documentation()
+                || this_network
+                || shared
+                || protocol
+                || benchmarking
+                || multicast_or_reserved)
+        }
+        IpAddr::V6(v6) => {
+            let segments = v6.segments();
+            let ipv4 = |high: u16, low: u16| {
+                IpAddr::V4(Ipv4Addr::from((u32::from(high) << 16) | u32::from(low)))
+            };
+            // Addresses carrying an IPv4 address are as public as it is:
+            // IPv4-mapped ::ffff:0:0/96, IPv4-compatible ::/96 (which also
+            // covers :: and ::1), NAT64 64:ff9b::/96 and 6to4 2002::/16
+            let mapped = segments[..6] == [0, 0, 0, 0, 0, 0xffff];
+            let compatible = segments[..6] == [0; 6];
+            let nat64 = segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0];
+            if mapped || compatible || nat64 {
+                return is_public_ip(ipv4(segments[6], segments[7]));
+            }
+            if segments[0] == 0x2002 {
+                return is_public_ip(ipv4(segments[1], segments[2]));
+            }
+            let first = segments[0];
+            let unique_local = (first & 0xfe00) == 0xfc00;
+            let link_local = (first & 0xffc0) == 0xfe80;
+            // Local-use NAT64, 64:ff9b:1::/48
+            let local_nat64 = segments[..3] == [0x64, 0xff9b, 1];
+            // Documentation, 2001:db8::/32
+            let documentation = segments[..2] == [0x2001, 0xdb8];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || unique_local
+                || link_local
+                || local_nat64
+                || documentation)
+        }
+    }
+}
+
+/// DNS resolver that drops non-public addresses, so host names pointing at
+/// the local network (including after redirects) can't be reached.
+struct PublicOnlyResolver;
+
+impl Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                let message = format!("{} resolves only to local addresses", name.as_str());
+                return Err(message.into());
+            }
+            Ok::<Addrs, Box<dyn std::error::Error + Send + Sync>>(Box::new(addrs.into_iter()))
+        })
+    }
+}
+
+/// HTTP client configured for `policy`, rebuilt only when the policy changes.
+pub fn client(policy: &FetchPolicy) -> reqwest::Client {
+    static CLIENT: Mutex<Option<(FetchPolicy, reqwest::Client)>> = Mutex::new(None);
+
+    let mut cached = CLIENT
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some((ref cached_policy, ref client)) = *cached {
+        if cached_policy == policy {
+            return client.clone();
+        }
+    }
+
+    let redirect_policy = policy.clone();
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .connect_timeout(Duration::from_secs(5))
+        .user_agent(policy.user_agent.clone())
+        .https_only(policy.https_only)
+        // A proxy would resolve host names itself, past `PublicOnlyResolver`
+        .no_proxy()
+        .redirect(reqwest::redirect::Policy::custom(
+            move |attempt| match redirect_policy
+                .check_redirect(attempt.url(), attempt.previous().len())
+            {
+                Ok(()) => attempt.follow(),
+                Err(reason) => attempt.error(reason),
+            },
+        ));
+    if !policy.allow_private_networks {
+        builder = builder.dns_resolver(Arc::new(PublicOnlyResolver));
+    }
+    let client = builder.build().unwrap_or_else(|_| reqwest::Client::new());
+
+    *cached = Some((policy.clone(), client.clone()));
+    client
+}
+
+/// Classify a failed request. Timeouts and connection problems may go away
+/// on their own; anything else, such as a refused redirect, won't.
+pub fn request_error(e: &reqwest::Error) -> ArtError {
+    if e.is_timeout() || e.is_connect() || e.is_body() {
+        ArtError::Transient
+    } else {
+        ArtError::Unavailable
+    }
+}
+
+/// Classify an error status. Server errors, timeouts and rate limiting are
+/// temporary; other client errors mean the art doesn't exist.
+pub fn status_error(status: reqwest::StatusCode) -> ArtError {
+    if status.is_server_error()
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+    {
+        ArtError::Transient
+    } else {
+        ArtError::Unavailable
+    }
+}
+
+/// Read an image response body, enforcing `max_size` while streaming and
+/// validating the content type and magic bytes.
+pub async fn read_image_body(
+    mut response: reqwest::Response,
+    max_size: usize,
+) -> Result<Vec<u8>, ArtError> {
+    if let Some(content_type) = response.headers().get(reqwest::header::CONTENT_TYPE) {
+        let content_type = content_type.to_str().unwrap_or_default();
+        // Some CDNs label images as generic binaries; magic bytes decide then
+        let acceptable = content_type.starts_with("image/")
+            || content_type.starts_with("application/octet-stream");
+        if !acceptable {
+            eprintln!("Album art rejected: unexpected content type '{content_type}'");
+            return Err(ArtError::Unavailable);
+        }
+    }
+
+    if let Some(length) = response.content_length() {
+        if length > u64::try_from(max_size).unwrap_or(u64::MAX) {
+            eprintln!("Album art download too large: {length} bytes");
+            return Err(ArtError::Unavailable);
+        }
+    }
+
+    let mut body = Vec::new();
+    loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                if body.len() + chunk.len() > max_size {
+                    eprintln!("Album art download exceeded {max_size} bytes, aborting");
+                    return Err(ArtError::Unavailable);
+                }
+                body.extend_from_slice(&chunk);
+            }
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("Failed to read album art response: {e}");
+                return Err(request_error(&e));
+            }
+        }
+    }
+
+    if image::guess_format(&body).is_err() {
+        eprintln!("Album art rejected: not a recognized image format");
+        return Err(ArtError::Unavailable);
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(url: &str) -> reqwest::Url {
+        reqwest::Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn blocked_addresses() {
+        for ip in [
+            "0.0.0.0",
+            "0.1.2.3",
+            "10.0.0.1",
+            "100.64.0.1",
+            "100.127.255.255",
+            "127.0.0.1",
+            "169.254.169.254",
+            "172.16.0.1",
+            "172.31.255.255",
+            "192.0.0.8",
+            "192.0.2.1",
+            "192.168.1.1",
+            "198.18.0.1",
+            "198.19.255.255",
+            "198.51.100.1",
+            "203.0.113.1",
+            "224.0.0.1",
+            "239.255.255.250",
+            "240.0.0.1",
+            "255.255.255.255",
+            "::",
+            "::1",
+            "::127.0.0.1",
+            "::ffff:127.0.0.1",
+            "::ffff:10.0.0.1",
+            "64:ff9b::7f00:1",
+            "64:ff9b::a9fe:a9fe",
+            "64:ff9b:1::1",
+            "2002:c0a8:101::1",
+            "2001:db8::1",
+            "fc00::1",
+            "fd12:3456::1",
+            "fe80::1",
+            "ff02::1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip} must be blocked");
+        }
+    }
+
+    #[test]
+    fn allowed_addresses() {
+        for ip in [
+            "1.1.1.1",
+            "8.8.8.8",
+            "100.63.255.255",
+            "100.128.0.1",
+            "172.32.0.1",
+            "192.0.1.1",
+            "198.17.255.255",
+            "198.20.0.1",
+            "223.255.255.254",
+            "::8.8.8.8",
+            "::ffff:8.8.8.8",
+            "64:ff9b::808:808",
+            "2002:808:808::1",
+            "2606:4700:4700::1111",
+        ] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{ip} must be allowed");
+        }
+    }
+
+    #[test]
+    fn urls_are_checked_against_the_policy() {
+        let default = FetchPolicy::default();
+        let https_only = FetchPolicy {
+            https_only: true,
+            ..FetchPolicy::default()
+        };
+        let private = FetchPolicy {
+            allow_private_networks: true,
+            ..FetchPolicy::default()
+        };
+        let offline = FetchPolicy {
+            offline: true,
+            ..FetchPolicy::default()
+        };
+
+        for (policy, target, allowed) in [
+            (&default, "https://coverartarchive.org/release/x", true),
+            (&default, "http://example.com/cover.jpg", true),
+            (&default, "https://8.8.8.8/cover.jpg", true),
+            (&default, "ftp://example.com/cover.jpg", false),
+            (&default, "file:///etc/passwd", false),
+            (&default, "http://127.0.0.1/cover.jpg", false),
+            (&default, "http://192.168.1.1:8080/cover.jpg", false),
+            (&default, "http://[::1]/cover.jpg", false),
+            (&default, "http://[::ffff:10.0.0.1]/cover.jpg", false),
+            (&default, "http://[64:ff9b::7f00:1]/cover.jpg", false),
+            (&https_only, "http://example.com/cover.jpg", false),
+            (&https_only, "https://example.com/cover.jpg", true),
+            (&private, "http://192.168.1.1/cover.jpg", true),
+            (&private, "http://[::1]/cover.jpg", true),
+            (&offline, "https://example.com/cover.jpg", false),
+        ] {
+            assert_eq!(
+                policy.check_url(&url(target)).is_ok(),
+                allowed,
+                "{target} with {policy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn redirects_are_checked_like_requests() {
+        let policy = FetchPolicy::default();
+        let public = url("https://example.com/cover.jpg");
+        assert!(policy.check_redirect(&public, 0).is_ok());
+        assert!(policy.check_redirect(&public, MAX_REDIRECTS - 1).is_ok());
+        assert!(policy.check_redirect(&public, MAX_REDIRECTS).is_err());
+        assert!(policy
+            .check_redirect(&url("http://169.254.169.254/latest/meta-data"), 1)
+            .is_err());
+        assert!(policy
+            .check_redirect(&url("file:///home/user/cover.jpg"), 1)
+            .is_err());
+
+        let https_only = FetchPolicy {
+            https_only: true,
+            ..FetchPolicy::default()
+        };
+        assert!(https_only
+            .check_redirect(&url("http://example.com/cover.jpg"), 1)
+            .is_err());
+    }
+}
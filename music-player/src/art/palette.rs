@@ -0,0 +1,248 @@
+//! Accent colors extracted from album art.
+//!
+//! The palette is computed once per art URL while decoding (see
+//! [`super::decode`]). Colors are adjusted against the active theme only when
+//! drawn, so switching between light and dark mode needs no recomputation.
+
+use cosmic::iced::Color;
+use image::DynamicImage;
+
+/// Edge length of the thumbnail sampled for colors.
+const SAMPLE_SIZE: u32 = 32;
+
+/// Minimum contrast ratio of accents against the background (WCAG 2 for
+/// non-text UI components).
+const MIN_ACCENT_CONTRAST: f32 = 3.0;
+
+/// Minimum contrast ratio of text on a tinted background.
+const MIN_TEXT_CONTRAST: f32 = 4.5;
+
+/// Colors picked from a cover.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArtPalette {
+    /// Average color of the most common hue region
+    pub dominant: Color,
+    /// Most saturated well-represented color; the dominant color for
+    /// greyscale covers
+    pub vibrant: Color,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Bucket {
+    count: u32,
+    sum: [u32; 3],
+}
+
+impl Bucket {
+    #[allow(clippy::cast_precision_loss)]
+    fn color(&self) -> Color {
+        let channel = |sum: u32| sum as f32 / self.count as f32 / 255.0;
+        Color::from_rgb(
+            channel(self.sum[0]),
+            channel(self.sum[1]),
+            channel(self.sum[2]),
+        )
+    }
+}
+
+/// Extract a palette by quantizing a small thumbnail to 4 bits per channel.
+///
+/// Returns `None` for fully transparent images.
+#[allow(clippy::cast_precision_loss)]
+pub fn extract(image: &DynamicImage) -> Option<ArtPalette> {
+    let sample = image.thumbnail(SAMPLE_SIZE, SAMPLE_SIZE).to_rgba8();
+
+    let mut buckets = vec![Bucket::default(); 4096];
+    let mut total = 0u32;
+    for pixel in sample.pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a < 128 {
+            continue;
+        }
+        let index = (usize::from(r >> 4) << 8) | (usize::from(g >> 4) << 4) | usize::from(b >> 4);
+        let bucket = &mut buckets[index];
+        bucket.count += 1;
+        bucket.sum[0] += u32::from(r);
+        bucket.sum[1] += u32::from(g);
+        bucket.sum[2] += u32::from(b);
+        total += 1;
+    }
+    if total == 0 {
+        return None;
+    }
+
+    let dominant = buckets.iter().max_by_key(|bucket| bucket.count)?.color();
+
+    // Favor saturated mid-lightness colors, ignoring specks under 1% of the cover
+    let vibrant = buckets
+        .iter()
+        .filter(|bucket| bucket.count * 100 >= total)
+        .map(|bucket| {
+            let color = bucket.color();
+            let (saturation, lightness) = saturation_lightness(color);
+            let share = bucket.count as f32 / total as f32;
+            let score = saturation * (1.0 - (lightness - 0.5).abs() * 2.0) * share.sqrt();
+            (score, saturation, color)
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .filter(|&(_, saturation, _)| saturation >= 0.2)
+        .map_or(dominant, |(_, _, color)| color);
+
+    Some(ArtPalette { dominant, vibrant })
+}
+
+/// HSL saturation and lightness.
+fn saturation_lightness(color: Color) -> (f32, f32) {
+    let max = color.r.max(color.g).max(color.b);
+    let min = color.r.min(color.g).min(color.b);
+    let lightness = (max + min) / 2.0;
+    let saturation = if (max - min).abs() < f32::EPSILON {
+        0.0
+    } else {
+        (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+    };
+    (saturation, lightness)
+}
+
+/// WCAG relative luminance.
+fn luminance(color: Color) -> f32 {
+    let linear = |c: f32| {
+        if c <= 0.039_28 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(color.r) + 0.7152 * linear(color.g) + 0.0722 * linear(color.b)
+}
+
+/// WCAG contrast ratio, from 1 to 21.
+fn contrast(a: Color, b: Color) -> f32 {
+    let (la, lb) = (luminance(a), luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+/// Linear blend of `from` towards `to` by `amount` (0 to 1).
+fn mix(from: Color, to: Color, amount: f32) -> Color {
+    Color::from_rgb(
+        from.r + (to.r - from.r) * amount,
+        from.g + (to.g - from.g) * amount,
+        from.b + (to.b - from.b) * amount,
+    )
+}
+
+impl ArtPalette {
+    /// Vibrant color lightened or darkened until it stands out against
+    /// `background`.
+    pub fn accent(&self, background: Color, is_dark: bool) -> Color {
+        let target = if is_dark { Color::WHITE } else { Color::BLACK };
+        (0u8..=10)
+            .map(|step| mix(self.vibrant, target, f32::from(step) / 10.0))
+            .find(|&color| contrast(color, background) >= MIN_ACCENT_CONTRAST)
+            .unwrap_or(target)
+    }
+
+    /// `background` subtly tinted with the dominant color, backing off as far
+    /// as needed to keep `text` readable.
+    pub fn tint(&self, background: Color, text: Color) -> Color {
+        [0.18, 0.12, 0.06]
+            .into_iter()
+            .map(|amount| mix(background, self.dominant, amount))
+            .find(|&color| contrast(color, text) >= MIN_TEXT_CONTRAST)
+            .unwrap_or(background)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Background and text colors of typical light and dark themes.
+    const THEMES: [(Color, Color, bool); 2] = [
+        (
+            Color::from_rgb(0.96, 0.96, 0.96),
+            Color::from_rgb(0.1, 0.1, 0.1),
+            false,
+        ),
+        (
+            Color::from_rgb(0.11, 0.11, 0.11),
+            Color::from_rgb(0.9, 0.9, 0.9),
+            true,
+        ),
+    ];
+
+    /// A coarse grid over the RGB cube, including black, white and greys.
+    fn sample_colors() -> impl Iterator<Item = Color> {
+        let steps = [0u8, 51, 102, 153, 204, 255];
+        steps.into_iter().flat_map(move |r| {
+            steps
+                .into_iter()
+                .flat_map(move |g| steps.into_iter().map(move |b| Color::from_rgb8(r, g, b)))
+        })
+    }
+
+    #[test]
+    fn contrast_matches_wcag() {
+        assert!((contrast(Color::BLACK, Color::WHITE) - 21.0).abs() < 0.01);
+        assert!((contrast(Color::WHITE, Color::BLACK) - 21.0).abs() < 0.01);
+        assert!((contrast(Color::WHITE, Color::WHITE) - 1.0).abs() < f32::EPSILON);
+        // #777 on white is the classic borderline case, just under 4.5
+        let grey = Color::from_rgb8(0x77, 0x77, 0x77);
+        assert!((contrast(grey, Color::WHITE) - 4.48).abs() < 0.01);
+    }
+
+    #[test]
+    fn accents_stand_out_on_light_and_dark_backgrounds() {
+        for (background, _, is_dark) in THEMES {
+            for vibrant in sample_colors() {
+                let palette = ArtPalette {
+                    dominant: vibrant,
+                    vibrant,
+                };
+                let accent = palette.accent(background, is_dark);
+                assert!(
+                    contrast(accent, background) >= MIN_ACCENT_CONTRAST,
+                    "{vibrant:?} on {background:?} gave {accent:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn accents_keep_colors_that_already_stand_out() {
+        let (background, _, is_dark) = THEMES[0];
+        let palette = ArtPalette {
+            dominant: Color::BLACK,
+            vibrant: Color::from_rgb8(0x00, 0x33, 0x99),
+        };
+        assert_eq!(palette.accent(background, is_dark), palette.vibrant);
+    }
+
+    #[test]
+    fn tints_keep_text_readable_on_light_and_dark_backgrounds() {
+        for (background, text, _) in THEMES {
+            assert!(contrast(background, text) >= MIN_TEXT_CONTRAST);
+            for dominant in sample_colors() {
+                let palette = ArtPalette {
+                    dominant,
+                    vibrant: dominant,
+                };
+                let tint = palette.tint(background, text);
+                assert!(
+                    contrast(tint, text) >= MIN_TEXT_CONTRAST,
+                    "{dominant:?} on {background:?} gave {tint:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tints_are_visible_when_contrast_allows() {
+        let (background, text, _) = THEMES[1];
+        let palette = ArtPalette {
+            dominant: Color::from_rgb8(0x33, 0x00, 0x66),
+            vibrant: Color::from_rgb8(0x33, 0x00, 0x66),
+        };
+        assert_ne!(palette.tint(background, text), background);
+    }
+}
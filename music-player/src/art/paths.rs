@@ -0,0 +1,193 @@
+//! Rules for which local files may be read as album art.
+//!
+//! `file://` art URLs come from MPRIS metadata, so reads are limited to an
+//! allowlist of directories to prevent arbitrary local file disclosure. A
+//! deny list takes precedence over it, e.g. to block a subfolder of an
+//! allowed one.
+
+use std::path::{Path, PathBuf};
+
+/// Allowed and denied directory prefixes, snapshotted from the config.
+#[derive(Debug, Clone)]
+pub struct PathPolicy {
+    pub allowed: Vec<PathBuf>,
+    pub denied: Vec<PathBuf>,
+}
+
+impl Default for PathPolicy {
+    fn default() -> Self {
+        Self {
+            allowed: default_allowed_paths(),
+            denied: Vec::new(),
+        }
+    }
+}
+
+impl PathPolicy {
+    /// Check a canonical path, returning a user-facing reason on rejection.
+    pub fn check(&self, path: &Path) -> Result<(), String> {
+        let folder = path.parent().unwrap_or(path).display();
+        if self.denied.iter().any(|prefix| path.starts_with(prefix)) {
+            return Err(format!("{folder} is in a denied folder"));
+        }
+        if !self.allowed.iter().any(|prefix| path.starts_with(prefix)) {
+            return Err(format!("{folder} is not in an allowed folder"));
+        }
+        Ok(())
+    }
+}
+
+/// Directories art may be read from unless configured otherwise: the user's
+/// cache and data dirs (where players put downloaded covers), media folders
+/// and the system temp dirs.
+pub fn default_allowed_paths() -> Vec<PathBuf> {
+    vec![
+        // XDG directories (covers ~/.cache, ~/.local/share, etc.)
+        dirs::cache_dir(),
+        dirs::data_dir(),
+        dirs::data_local_dir(),
+        dirs::runtime_dir(),
+        // Common music/media directories
+        dirs::audio_dir(),
+        dirs::picture_dir(),
+        dirs::home_dir().map(|h| h.join("Music")),
+        dirs::home_dir().map(|h| h.join(".music")),
+        // System temp
+        Some(PathBuf::from("/tmp")),
+        Some(PathBuf::from("/var/tmp")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Turn a folder typed in Settings into a config entry.
+///
+/// A leading `~` is expanded and the path must be absolute. Existing folders
+/// are canonicalized, since art paths are compared after resolving symlinks;
+/// missing ones (e.g. an unmounted share) are kept as typed.
+pub fn parse_folder(input: &str) -> Option<PathBuf> {
+    let input = input.trim();
+    let path = if input == "~" {
+        dirs::home_dir()?
+    } else if let Some(rest) = input.strip_prefix("~/") {
+        dirs::home_dir()?.join(rest)
+    } else {
+        PathBuf::from(input)
+    };
+    if !path.is_absolute() {
+        return None;
+    }
+    Some(std::fs::canonicalize(&path).unwrap_or(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allowed: &[&str], denied: &[&str]) -> PathPolicy {
+        PathPolicy {
+            allowed: allowed.iter().map(PathBuf::from).collect(),
+            denied: denied.iter().map(PathBuf::from).collect(),
+        }
+    }
+
+    fn allows(policy: &PathPolicy, path: &str) -> bool {
+        policy.check(Path::new(path)).is_ok()
+    }
+
+    #[test]
+    fn prefixes_match_whole_components() {
+        let policy = policy(&["/home/a"], &[]);
+        assert!(allows(&policy, "/home/a"));
+        assert!(allows(&policy, "/home/a/cover.jpg"));
+        assert!(allows(&policy, "/home/a/Music/Album/cover.jpg"));
+        assert!(!allows(&policy, "/home/ab/cover.jpg"));
+        assert!(!allows(&policy, "/home/a.jpg"));
+        assert!(!allows(&policy, "/home/cover.jpg"));
+        assert!(!allows(&policy, "/etc/passwd"));
+    }
+
+    #[test]
+    fn trailing_slashes_do_not_matter() {
+        let policy = policy(&["/home/a/"], &["/home/a/private/"]);
+        assert!(allows(&policy, "/home/a/cover.jpg"));
+        assert!(!allows(&policy, "/home/ab/cover.jpg"));
+        assert!(!allows(&policy, "/home/a/private/cover.jpg"));
+        assert!(allows(&policy, "/home/a/private2/cover.jpg"));
+    }
+
+    #[test]
+    fn deny_beats_allow() {
+        let policy = policy(&["/home/a"], &["/home/a/.ssh", "/home/a"]);
+        let error = policy.check(Path::new("/home/a/cover.jpg")).unwrap_err();
+        assert!(error.contains("denied"), "{error}");
+
+        let policy = PathPolicy {
+            denied: vec![PathBuf::from("/home/a/.ssh")],
+            ..policy
+        };
+        assert!(allows(&policy, "/home/a/cover.jpg"));
+        assert!(!allows(&policy, "/home/a/.ssh/id_ed25519"));
+        assert!(!allows(&policy, "/home/a/.ssh"));
+    }
+
+    #[test]
+    fn nothing_is_allowed_without_allowed_folders() {
+        let policy = policy(&[], &[]);
+        let error = policy.check(Path::new("/tmp/cover.jpg")).unwrap_err();
+        assert!(error.contains("not in an allowed folder"), "{error}");
+    }
+
+    #[test]
+    fn folders_must_be_absolute() {
+        for input in ["", "   ", "Music", "./Music", "../Music", "~user/Music"] {
+            assert_eq!(parse_folder(input), None, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn tilde_expands_to_home() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(
+            parse_folder("~"),
+            Some(std::fs::canonicalize(&home).unwrap_or(home.clone()))
+        );
+        assert_eq!(
+            parse_folder(" ~/no-such-folder-for-art-tests "),
+            Some(home.join("no-such-folder-for-art-tests"))
+        );
+    }
+
+    #[test]
+    fn missing_folders_are_kept_as_typed() {
+        assert_eq!(
+            parse_folder("/no/such/folder/"),
+            Some(PathBuf::from("/no/such/folder"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn existing_folders_are_canonicalized() {
+        let dir =
+            std::env::temp_dir().join(format!("music-player-paths-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let target = dir.join("target");
+        std::fs::create_dir_all(&target).unwrap();
+        let link = dir.join("link");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let canonical = std::fs::canonicalize(&target).unwrap();
+        assert_eq!(
+            parse_folder(link.to_str().unwrap()),
+            Some(canonical.clone())
+        );
+        assert_eq!(
+            parse_folder(&format!("{}/../target/", link.display())),
+            Some(canonical)
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
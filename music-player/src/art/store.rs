@@ -0,0 +1,201 @@
+//! URL-keyed in-memory album art store shared by single- and multi-player mode.
+//!
+//! The store only tracks state; fetching is done by the app. It coalesces
+//! requests for a URL that is already loading, remembers permanent failures so
+//! they are not retried on every poll, backs off on transient ones, and lets
+//! the app drop results for URLs that are no longer shown.
+
+use super::decode::ArtImage;
+use super::palette::ArtPalette;
+use super::ArtError;
+use cosmic::iced::widget::image::Handle;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Delay before retrying after the first transient failure; doubles with
+/// every further failure.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+/// Upper bound for the retry delay.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Copy)]
+pub enum ArtState<'a> {
+    Loaded(&'a Handle),
+    Loading,
+    /// Art could not be loaded; carries the reason if it was blocked by
+    /// the user's settings
+    Failed(Option<&'a str>),
+}
+
+/// Backoff state of a URL whose last fetch failed transiently.
+#[derive(Debug, Clone, Copy)]
+struct Retry {
+    attempts: u32,
+    next: Instant,
+}
+
+#[derive(Default)]
+pub struct ArtStore {
+    loaded: HashMap<String, ArtImage>,
+    in_flight: HashSet<String>,
+    failed: HashMap<String, Option<String>>,
+    retries: HashMap<String, Retry>,
+}
+
+impl ArtStore {
+    /// State of the art at `url`, with the handle pre-scaled closest to `size`.
+    pub fn state(&self, url: &str, size: u16) -> ArtState<'_> {
+        if let Some(handle) = self.loaded.get(url).and_then(|art| art.handle(size)) {
+            ArtState::Loaded(handle)
+        } else if let Some(reason) = self.failed.get(url) {
+            ArtState::Failed(reason.as_deref())
+        } else if self.retries.contains_key(url) {
+            // Keep the placeholder up between retries instead of flickering
+            ArtState::Failed(None)
+        } else {
+            ArtState::Loading
+        }
+    }
+
+    /// Whether `url` is neither loaded, loading, known to fail nor waiting
+    /// for its next retry.
+    pub fn needs_fetch(&self, url: &str) -> bool {
+        !self.loaded.contains_key(url)
+            && !self.in_flight.contains(url)
+            && !self.failed.contains_key(url)
+            && self
+                .retries
+                .get(url)
+                .map_or(true, |retry| Instant::now() >= retry.next)
+    }
+
+    /// Mark `url` as in flight. Returns `false` if it is already loaded,
+    /// loading, known to fail or backing off, in which case no fetch should
+    /// be started.
+    pub fn begin(&mut self, url: &str) -> bool {
+        if !self.needs_fetch(url) {
+            return false;
+        }
+        self.in_flight.insert(url.to_string())
+    }
+
+    /// Record the result of a fetch started with [`ArtStore::begin`].
+    ///
+    /// Results for URLs that are no longer wanted (the track changed while the
+    /// fetch was running) are discarded.
+    pub fn finish(&mut self, url: String, art: Result<ArtImage, ArtError>, still_wanted: bool) {
+        self.in_flight.remove(&url);
+        if !still_wanted {
+            return;
+        }
+        let retry = self.retries.remove(&url);
+        match art {
+            Ok(art) => {
+                self.loaded.insert(url, art);
+            }
+            Err(ArtError::Rejected(reason)) => {
+                self.failed.insert(url, Some(reason));
+            }
+            Err(ArtError::Unavailable) => {
+                self.failed.insert(url, None);
+            }
+            Err(ArtError::Transient) => {
+                let attempts = retry.map_or(1, |retry| retry.attempts + 1);
+                let next = Instant::now() + retry_delay(attempts);
+                self.retries.insert(url, Retry { attempts, next });
+            }
+        }
+    }
+
+    /// Forget loaded art and failures for URLs no player shows anymore.
+    pub fn retain(&mut self, wanted: &HashSet<&str>) {
+        self.loaded.retain(|url, _| wanted.contains(url.as_str()));
+        self.failed.retain(|url, _| wanted.contains(url.as_str()));
+        self.retries.retain(|url, _| wanted.contains(url.as_str()));
+    }
+
+    /// Allow failed URLs to be fetched again, e.g. after the network policy
+    /// was relaxed.
+    pub fn clear_failed(&mut self) {
+        self.failed.clear();
+        self.retries.clear();
+    }
+
+    /// Accent palette of the loaded art at `url`.
+    pub fn palette(&self, url: &str) -> Option<ArtPalette> {
+        self.loaded.get(url).and_then(ArtImage::palette)
+    }
+}
+
+/// Backoff before the next attempt after `attempts` transient failures.
+fn retry_delay(attempts: u32) -> Duration {
+    let factor = 1u32
+        .checked_shl(attempts.saturating_sub(1))
+        .unwrap_or(u32::MAX);
+    RETRY_DELAY.saturating_mul(factor).min(MAX_RETRY_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://example.com/cover.jpg";
+
+    fn fail(store: &mut ArtStore, error: ArtError) {
+        assert!(store.begin(URL));
+        store.finish(URL.to_string(), Err(error), true);
+    }
+
+    #[test]
+    fn permanent_failures_are_cached() {
+        let mut store = ArtStore::default();
+        fail(&mut store, ArtError::Unavailable);
+        assert!(!store.needs_fetch(URL));
+        assert!(matches!(store.state(URL, 64), ArtState::Failed(None)));
+
+        let mut store = ArtStore::default();
+        fail(&mut store, ArtError::Rejected("blocked".to_string()));
+        assert!(!store.needs_fetch(URL));
+        assert!(matches!(
+            store.state(URL, 64),
+            ArtState::Failed(Some("blocked"))
+        ));
+    }
+
+    #[test]
+    fn transient_failures_back_off_then_retry() {
+        let mut store = ArtStore::default();
+        fail(&mut store, ArtError::Transient);
+        assert!(!store.needs_fetch(URL));
+        assert!(!store.failed.contains_key(URL));
+        assert!(matches!(store.state(URL, 64), ArtState::Failed(None)));
+
+        // Once the backoff has passed the URL is fetched again
+        store.retries.get_mut(URL).unwrap().next = Instant::now();
+        fail(&mut store, ArtError::Transient);
+        assert_eq!(store.retries[URL].attempts, 2);
+
+        // A permanent failure ends the retries
+        store.retries.get_mut(URL).unwrap().next = Instant::now();
+        fail(&mut store, ArtError::Unavailable);
+        assert!(!store.retries.contains_key(URL));
+        assert!(!store.needs_fetch(URL));
+    }
+
+    #[test]
+    fn clearing_failures_also_resets_backoff() {
+        let mut store = ArtStore::default();
+        fail(&mut store, ArtError::Transient);
+        store.clear_failed();
+        assert!(store.needs_fetch(URL));
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_the_cap() {
+        assert_eq!(retry_delay(1), RETRY_DELAY);
+        assert_eq!(retry_delay(2), RETRY_DELAY * 2);
+        assert_eq!(retry_delay(4), RETRY_DELAY * 8);
+        assert_eq!(retry_delay(10), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(u32::MAX), MAX_RETRY_DELAY);
+    }
+}
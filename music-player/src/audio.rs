@@ -1,145 +1,480 @@
+//! Per-application stream volume through PulseAudio/PipeWire.
+//!
+//! Used for players (mostly browsers) whose MPRIS interface has no working
+//! volume. The native libpulse backend (see [`pulse`]) keeps the sink input
+//! table current from server events; if it can't connect, `pactl` (see
+//! [`pactl`]) is polled instead.
+//!
+//! Streams are attributed to players by process ID (see [`process`]), with
+//! name matching as a last resort. The native backend can also monitor
+//! streams for their peak and RMS levels and recent samples, which
+//! [`spectrum`] turns into visualizer bars.
+
+mod pactl;
+pub mod process;
+mod pulse;
+pub mod spectrum;
+
 use anyhow::Result;
+use pulse::{
+    DefaultSink, LevelTable, PulseBackend, SampleTable, SinkInputTable, SinkTable, Tables,
+};
 use std::collections::HashMap;
-use std::process::Command;
 use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
 
 #[derive(Debug, Clone)]
 pub struct AudioSinkInput {
     pub index: u32,
     pub application_name: String,
+    /// Linear volume, 1.0 = 100%
+    pub volume: f64,
+    pub muted: bool,
+    /// Index of the sink (output device) the stream plays on
+    pub sink: u32,
+    /// `application.process.id` reported by the client
+    pub process_id: Option<u32>,
+    /// `application.icon_name` reported by the client
+    pub icon_name: Option<String>,
+}
+
+/// An output device.
+#[derive(Debug, Clone)]
+pub struct AudioSink {
+    pub index: u32,
+    /// Name used to address the sink, e.g. `alsa_output.pci-0000_00_1f.3.analog-stereo`
+    pub name: String,
+    /// Human-readable name, e.g. "Built-in Audio Analog Stereo"
+    pub description: String,
+    /// Linear volume, 1.0 = 100%
     pub volume: f64,
+    pub muted: bool,
+}
+
+/// Loudness of a stream over its latest recorded fragment, as linear sample
+/// magnitudes (1.0 = full scale).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AudioLevel {
+    pub peak: f32,
+    pub rms: f32,
+}
+
+impl AudioLevel {
+    pub fn from_samples(samples: impl IntoIterator<Item = f32>) -> Self {
+        let (mut peak, mut sum, mut count) = (0.0_f32, 0.0_f32, 0_u32);
+        for sample in samples {
+            peak = peak.max(sample.abs());
+            sum += sample * sample;
+            count += 1;
+        }
+        let rms = if count == 0 {
+            0.0
+        } else {
+            (sum / count as f32).sqrt()
+        };
+        Self { peak, rms }
+    }
+}
+
+/// Sample rate streams are monitored at, for levels and the spectrum.
+pub const MONITOR_RATE: u32 = 22_050;
+
+/// Highest volume the backends set (150%), for boosting quiet streams.
+pub const MAX_BOOST_VOLUME: f64 = 1.5;
+
+/// Sink name the server resolves to the current default output.
+pub const DEFAULT_SINK: &str = "@DEFAULT_SINK@";
+
+/// How a stream was attributed to a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamMatch {
+    /// The stream belongs to the player's process or one of its children
+    ProcessId,
+    /// The stream's application name resembles the player's identity
+    Name,
+}
+
+enum Backend {
+    Pulse(PulseBackend),
+    Pactl,
 }
 
 pub struct AudioController {
-    sink_inputs: Arc<Mutex<HashMap<u32, AudioSinkInput>>>,
+    sink_inputs: SinkInputTable,
+    sinks: SinkTable,
+    default_sink: DefaultSink,
+    levels: LevelTable,
+    samples: SampleTable,
+    backend: Backend,
+    /// Notified when the native backend sees sink inputs change
+    changes: Arc<Notify>,
 }
 
 impl AudioController {
     pub fn new() -> Self {
+        let sink_inputs: SinkInputTable = Arc::new(Mutex::new(HashMap::new()));
+        let sinks: SinkTable = Arc::new(Mutex::new(HashMap::new()));
+        let default_sink: DefaultSink = Arc::new(Mutex::new(None));
+        let levels: LevelTable = Arc::new(Mutex::new(HashMap::new()));
+        let samples: SampleTable = Arc::new(Mutex::new(HashMap::new()));
+        let changes = Arc::new(Notify::new());
+
+        let tables = Tables {
+            sink_inputs: Arc::clone(&sink_inputs),
+            sinks: Arc::clone(&sinks),
+            default_sink: Arc::clone(&default_sink),
+            levels: Arc::clone(&levels),
+            samples: Arc::clone(&samples),
+        };
+        let backend = match PulseBackend::spawn(tables, Arc::clone(&changes)) {
+            Ok(pulse) => Backend::Pulse(pulse),
+            Err(e) => {
+                eprintln!("Failed to start the PulseAudio thread: {e}; falling back to pactl");
+                Backend::Pactl
+            }
+        };
+
         Self {
-            sink_inputs: Arc::new(Mutex::new(HashMap::new())),
+            sink_inputs,
+            sinks,
+            default_sink,
+            levels,
+            samples,
+            backend,
+            changes,
         }
     }
 
+    /// The native backend, unless it could not connect at startup.
+    fn pulse(&self) -> Option<&PulseBackend> {
+        match self.backend {
+            Backend::Pulse(ref pulse) if !pulse.failed() => Some(pulse),
+            _ => None,
+        }
+    }
+
+    /// Notifier for sink, sink input and default sink changes, if they are
+    /// pushed by the server.
+    ///
+    /// `None` means the tables are only updated by [`Self::refresh_sink_inputs`].
+    pub fn changes(&self) -> Option<Arc<Notify>> {
+        self.pulse().map(|_| Arc::clone(&self.changes))
+    }
+
+    /// Update the sink and sink input tables and the default sink. A no-op
+    /// for the native backend, which is kept current by server events.
     pub fn refresh_sink_inputs(&self) -> Result<()> {
-        // Use pactl to list sink inputs
-        let output = Command::new("pactl")
-            .arg("list")
-            .arg("sink-inputs")
-            .output()?;
-
-        if !output.status.success() {
-            return Err(anyhow::anyhow!("pactl command failed"));
+        if self.pulse().is_none() {
+            let listed = pactl::list_sink_inputs()?;
+            *self
+                .sink_inputs
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner) = listed;
+            let listed = pactl::list_sinks()?;
+            *self
+                .sinks
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner) = listed;
+            let default_sink = pactl::default_sink()?;
+            *self
+                .default_sink
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner) = default_sink;
         }
+        Ok(())
+    }
+
+    /// Name of the default output device, once known.
+    pub fn default_sink(&self) -> Option<String> {
+        self.default_sink
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Output devices, sorted by index.
+    pub fn sinks(&self) -> Vec<AudioSink> {
+        let mut sinks: Vec<AudioSink> = self
+            .sinks
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .values()
+            .cloned()
+            .collect();
+        sinks.sort_by_key(|sink| sink.index);
+        sinks
+    }
+
+    /// All sink inputs, sorted by index.
+    pub fn sink_inputs(&self) -> Vec<AudioSinkInput> {
+        let mut sink_inputs: Vec<AudioSinkInput> = self
+            .sink_inputs
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .values()
+            .cloned()
+            .collect();
+        sink_inputs.sort_by_key(|sink_input| sink_input.index);
+        sink_inputs
+    }
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        let mut sink_inputs = self
+    /// Find all streams of a player (browsers open one per tab). Sorted by
+    /// index; see [`match_player_streams`].
+    pub fn find_player_streams(
+        &self,
+        process_id: Option<u32>,
+        identity: &str,
+    ) -> Option<(Vec<AudioSinkInput>, StreamMatch)> {
+        let sink_inputs = self
             .sink_inputs
             .lock()
             .unwrap_or_else(std::sync::PoisonError::into_inner);
-        sink_inputs.clear();
-
-        let mut current_index: Option<u32> = None;
-        let mut current_app_name = String::new();
-        let mut current_volume = 1.0;
-
-        for line in output_str.lines() {
-            let line = line.trim();
-
-            if line.starts_with("Sink Input #") {
-                // Save previous entry if exists
-                if let Some(index) = current_index {
-                    sink_inputs.insert(
-                        index,
-                        AudioSinkInput {
-                            index,
-                            application_name: current_app_name.clone(),
-                            volume: current_volume,
-                        },
-                    );
-                }
-
-                // Start new entry
-                if let Some(index_str) = line.strip_prefix("Sink Input #") {
-                    current_index = index_str.parse().ok();
-                    current_app_name = String::new();
-                    current_volume = 1.0;
-                }
-            } else if line.starts_with("application.name = ") {
-                current_app_name = line
-                    .strip_prefix("application.name = \"")
-                    .and_then(|s| s.strip_suffix("\""))
-                    .unwrap_or("")
-                    .to_string();
-            } else if line.starts_with("Volume:") {
-                // Parse volume percentage (e.g., "Volume: front-left: 65536 / 100%")
-                if let Some(percent_pos) = line.find('%') {
-                    let before_percent = &line[..percent_pos];
-                    if let Some(last_space) = before_percent.rfind(' ') {
-                        if let Ok(percent) = before_percent[last_space + 1..].trim().parse::<f64>()
-                        {
-                            current_volume = percent / 100.0;
-                        }
-                    }
-                }
-            }
+
+        let mut candidates: Vec<&AudioSinkInput> = sink_inputs.values().collect();
+        candidates.sort_by_key(|sink_input| sink_input.index);
+        match_player_streams(&candidates, process_id, identity)
+    }
+
+    /// Set the volume of all streams of one player: the loudest gets `volume`
+    /// and the others keep their level relative to it.
+    pub fn set_streams_volume(&self, streams: &[AudioSinkInput], volume: f64) -> Result<()> {
+        let loudest = streams.iter().map(|s| s.volume).fold(0.0, f64::max);
+        for stream in streams {
+            let target = if loudest > 0.0 {
+                stream.volume / loudest * volume
+            } else {
+                volume
+            };
+            self.set_sink_input_volume(stream.index, target)?;
         }
+        Ok(())
+    }
 
-        // Save last entry
-        if let Some(index) = current_index {
-            sink_inputs.insert(
-                index,
-                AudioSinkInput {
-                    index,
-                    application_name: current_app_name,
-                    volume: current_volume,
-                },
-            );
+    /// Set a sink input's volume (clamped to 0.0-1.5, i.e. up to 150%).
+    pub fn set_sink_input_volume(&self, index: u32, volume: f64) -> Result<()> {
+        match self.pulse() {
+            Some(pulse) => pulse.set_volume(index, volume),
+            None => pactl::set_sink_input_volume(index, volume),
         }
+    }
 
-        Ok(())
+    pub fn set_sink_input_mute(&self, index: u32, muted: bool) -> Result<()> {
+        match self.pulse() {
+            Some(pulse) => pulse.set_mute(index, muted),
+            None => pactl::set_sink_input_mute(index, muted),
+        }
     }
 
-    pub fn find_sink_input_by_name(&self, app_name_pattern: &str) -> Option<AudioSinkInput> {
-        let sink_inputs = self
-            .sink_inputs
+    /// Move a sink input to another sink, by name (or [`DEFAULT_SINK`]).
+    pub fn move_sink_input(&self, index: u32, sink_name: &str) -> Result<()> {
+        match self.pulse() {
+            Some(pulse) => pulse.move_sink_input(index, sink_name),
+            None => pactl::move_sink_input(index, sink_name),
+        }
+    }
+
+    /// Set a sink's volume (clamped to 0.0-1.5, i.e. up to 150%).
+    pub fn set_sink_volume(&self, index: u32, volume: f64) -> Result<()> {
+        match self.pulse() {
+            Some(pulse) => pulse.set_sink_volume(index, volume),
+            None => pactl::set_sink_volume(index, volume),
+        }
+    }
+
+    pub fn set_sink_mute(&self, index: u32, muted: bool) -> Result<()> {
+        match self.pulse() {
+            Some(pulse) => pulse.set_sink_mute(index, muted),
+            None => pactl::set_sink_mute(index, muted),
+        }
+    }
+
+    pub fn set_default_sink(&self, sink_name: &str) -> Result<()> {
+        match self.pulse() {
+            Some(pulse) => pulse.set_default_sink(sink_name),
+            None => pactl::set_default_sink(sink_name),
+        }
+    }
+
+    /// Keep the levels of exactly these streams current; an empty list stops
+    /// monitoring. Levels need the native backend, so this is a no-op with
+    /// `pactl`.
+    pub fn monitor_streams(&self, indices: &[u32]) -> Result<()> {
+        match self.pulse() {
+            Some(pulse) => pulse.monitor(indices),
+            None => Ok(()),
+        }
+    }
+
+    /// Latest level of each monitored stream.
+    pub fn levels(&self) -> HashMap<u32, AudioLevel> {
+        self.levels
             .lock()
-            .unwrap_or_else(std::sync::PoisonError::into_inner);
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
 
-        let pattern_lower = app_name_pattern.to_lowercase();
+    /// The most recent samples of a monitored stream, oldest first.
+    pub fn samples(&self, index: u32) -> Option<Vec<f32>> {
+        self.samples
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&index)
+            .map(|history| history.iter().copied().collect())
+    }
+}
 
-        sink_inputs
-            .values()
-            .find(|sink_input| {
-                let app_name_lower = sink_input.application_name.to_lowercase();
-                app_name_lower.contains(&pattern_lower) || pattern_lower.contains(&app_name_lower)
+/// Streams of a player among `candidates`.
+///
+/// Streams of the player's process or its children match first, when its
+/// `process_id` (the process owning its D-Bus name) is known. As a last
+/// resort, streams match when the words of their application name and of the
+/// player's identity line up, so "Music" matches "GNOME Music" but not
+/// "MusicBox". Sandboxed (Flatpak, Snap) players report process IDs from
+/// inside their sandbox, so only the name match finds their streams.
+fn match_player_streams(
+    candidates: &[&AudioSinkInput],
+    process_id: Option<u32>,
+    identity: &str,
+) -> Option<(Vec<AudioSinkInput>, StreamMatch)> {
+    let collect = |matches: Vec<AudioSinkInput>, matched_by| {
+        (!matches.is_empty()).then_some((matches, matched_by))
+    };
+
+    if let Some(player_pid) = process_id {
+        let by_pid = candidates
+            .iter()
+            .filter(|sink_input| {
+                sink_input
+                    .process_id
+                    .is_some_and(|pid| process::is_same_or_descendant(pid, player_pid))
             })
-            .cloned()
+            .map(|sink_input| (*sink_input).clone())
+            .collect();
+        if let Some(found) = collect(by_pid, StreamMatch::ProcessId) {
+            return Some(found);
+        }
     }
 
-    #[allow(
-        clippy::cast_possible_truncation,
-        clippy::cast_sign_loss,
-        clippy::unused_self
-    )]
-    pub fn set_sink_input_volume(&self, index: u32, volume: f64) -> Result<()> {
-        // Clamp volume to 0.0-1.5 (150%)
-        let clamped_volume = volume.clamp(0.0, 1.5);
+    let identity_words = words(identity);
+    let by_name = candidates
+        .iter()
+        .filter(|sink_input| {
+            let name_words = words(&sink_input.application_name);
+            contains_words(&name_words, &identity_words)
+                || contains_words(&identity_words, &name_words)
+        })
+        .map(|sink_input| (*sink_input).clone())
+        .collect();
+    collect(by_name, StreamMatch::Name)
+}
 
-        // Convert to percentage (safe: clamped range guarantees 0..=150)
-        let volume_percent = (clamped_volume * 100.0).round() as u32;
+/// Lowercase alphanumeric words of a name.
+fn words(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether `needle` occurs in `haystack` as a run of whole words.
+fn contains_words(haystack: &[String], needle: &[String]) -> bool {
+    !needle.is_empty()
+        && haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+}
 
-        // Use pactl to set volume
-        let output = Command::new("pactl")
-            .arg("set-sink-input-volume")
-            .arg(index.to_string())
-            .arg(format!("{volume_percent}%"))
-            .output()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if !output.status.success() {
-            return Err(anyhow::anyhow!("pactl set-sink-input-volume failed"));
+    /// Far above any real PID, so no process tree is found in `/proc`.
+    const MISSING_PID: u32 = 4_000_000_000;
+
+    fn stream(index: u32, application_name: &str, process_id: Option<u32>) -> AudioSinkInput {
+        AudioSinkInput {
+            index,
+            application_name: application_name.to_string(),
+            volume: 1.0,
+            muted: false,
+            sink: 0,
+            process_id,
+            icon_name: None,
         }
+    }
 
-        Ok(())
+    fn indices(
+        found: Option<(Vec<AudioSinkInput>, StreamMatch)>,
+    ) -> Option<(Vec<u32>, StreamMatch)> {
+        found.map(|(streams, matched_by)| (streams.iter().map(|s| s.index).collect(), matched_by))
+    }
+
+    #[test]
+    fn known_pid_matches_its_streams() {
+        let streams = [
+            stream(1, "Firefox", Some(MISSING_PID)),
+            stream(2, "Firefox", Some(MISSING_PID - 1)),
+            stream(3, "Firefox", Some(MISSING_PID)),
+        ];
+        let candidates: Vec<&AudioSinkInput> = streams.iter().collect();
+        assert_eq!(
+            indices(match_player_streams(
+                &candidates,
+                Some(MISSING_PID),
+                "Mozilla Firefox"
+            )),
+            Some((vec![1, 3], StreamMatch::ProcessId))
+        );
+    }
+
+    #[test]
+    fn sandboxed_player_falls_back_to_name() {
+        // A Flatpak reports its PID from inside the sandbox, unrelated to
+        // the PID owning the player's D-Bus name
+        let streams = [
+            stream(1, "Spotify", Some(MISSING_PID - 1)),
+            stream(2, "MusicBox", Some(MISSING_PID - 2)),
+        ];
+        let candidates: Vec<&AudioSinkInput> = streams.iter().collect();
+        assert_eq!(
+            indices(match_player_streams(
+                &candidates,
+                Some(MISSING_PID),
+                "Spotify"
+            )),
+            Some((vec![1], StreamMatch::Name))
+        );
+        assert_eq!(
+            indices(match_player_streams(
+                &candidates,
+                Some(MISSING_PID),
+                "Music"
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn name_fallback_matches_whole_words() {
+        let streams = [
+            stream(1, "MusicBox", None),
+            stream(2, "GNOME Music", None),
+            stream(3, "Spotify", None),
+            stream(4, "Music Player Daemon", Some(MISSING_PID)),
+        ];
+        let candidates: Vec<&AudioSinkInput> = streams.iter().collect();
+        assert_eq!(
+            indices(match_player_streams(&candidates, None, "Music")),
+            Some((vec![2, 4], StreamMatch::Name))
+        );
+        assert_eq!(
+            indices(match_player_streams(&candidates, None, "spotify")),
+            Some((vec![3], StreamMatch::Name))
+        );
+        assert_eq!(
+            indices(match_player_streams(&candidates, None, "Rhythmbox")),
+            None
+        );
+        assert_eq!(indices(match_player_streams(&candidates, None, "")), None);
     }
 }
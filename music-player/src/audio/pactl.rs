@@ -0,0 +1,599 @@
+//! `pactl` subprocess backend, used when libpulse can't connect.
+//!
+//! Sinks and sink inputs are read from `pactl --format=json` where supported
+//! (pactl 16 and later). Older versions fall back to the text output, run
+//! under `LC_ALL=C` so the labels being parsed are not translated.
+
+use super::{AudioSink, AudioSinkInput};
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Linear volume of 100% in PulseAudio volume units.
+const VOLUME_NORM: f64 = 65536.0;
+
+/// Set once `pactl` turned out not to support JSON, to stop retrying it.
+static JSON_UNSUPPORTED: AtomicBool = AtomicBool::new(false);
+
+/// List the current sink inputs.
+pub fn list_sink_inputs() -> Result<HashMap<u32, AudioSinkInput>> {
+    list("sink-inputs", parse_json, parse_text)
+}
+
+/// List the current sinks.
+pub fn list_sinks() -> Result<HashMap<u32, AudioSink>> {
+    list("sinks", parse_sinks_json, parse_sinks_text)
+}
+
+/// Run `pactl list <kind>`, as JSON unless that is known not to work.
+fn list<T>(
+    kind: &str,
+    parse_json: fn(&str) -> Result<HashMap<u32, T>>,
+    parse_text: fn(&str) -> HashMap<u32, T>,
+) -> Result<HashMap<u32, T>> {
+    let mut json_error = None;
+    if !JSON_UNSUPPORTED.load(Ordering::Relaxed) {
+        let parsed =
+            run_pactl(&["--format=json", "list", kind]).and_then(|output| parse_json(&output));
+        match parsed {
+            Ok(listed) => return Ok(listed),
+            Err(e) => json_error = Some(e),
+        }
+    }
+
+    let listed = parse_text(&run_pactl(&["list", kind])?);
+
+    // Only blame the JSON mode once the text mode proved pactl works
+    if let Some(e) = json_error {
+        eprintln!("pactl JSON output unavailable, using text output: {e}");
+        JSON_UNSUPPORTED.store(true, Ordering::Relaxed);
+    }
+    Ok(listed)
+}
+
+fn run_pactl(args: &[&str]) -> Result<String> {
+    let output = Command::new("pactl")
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("pactl command failed"));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse the output of `pactl --format=json list sink-inputs`.
+fn parse_json(output: &str) -> Result<HashMap<u32, AudioSinkInput>> {
+    let entries: Vec<Value> = serde_json::from_str(output)?;
+    let mut sink_inputs = HashMap::new();
+
+    for entry in entries {
+        let Some(index) = json_index(&entry["index"]) else {
+            continue;
+        };
+
+        let application_name = entry["properties"]["application.name"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+
+        sink_inputs.insert(
+            index,
+            AudioSinkInput {
+                index,
+                application_name,
+                volume: json_volume(&entry["volume"]).unwrap_or(1.0),
+                muted: entry["mute"].as_bool().unwrap_or(false),
+                sink: json_index(&entry["sink"]).unwrap_or(u32::MAX),
+                process_id: entry["properties"]["application.process.id"]
+                    .as_str()
+                    .and_then(|pid| pid.parse().ok()),
+                icon_name: entry["properties"]["application.icon_name"]
+                    .as_str()
+                    .map(ToString::to_string),
+            },
+        );
+    }
+
+    Ok(sink_inputs)
+}
+
+/// Parse the output of `pactl --format=json list sinks`.
+fn parse_sinks_json(output: &str) -> Result<HashMap<u32, AudioSink>> {
+    let entries: Vec<Value> = serde_json::from_str(output)?;
+    let sinks = entries
+        .iter()
+        .filter_map(|entry| {
+            let index = json_index(&entry["index"])?;
+            let name = entry["name"].as_str()?.to_string();
+            let description = entry["description"]
+                .as_str()
+                .map_or_else(|| name.clone(), ToString::to_string);
+            Some((
+                index,
+                AudioSink {
+                    index,
+                    name,
+                    description,
+                    volume: json_volume(&entry["volume"]).unwrap_or(1.0),
+                    muted: entry["mute"].as_bool().unwrap_or(false),
+                },
+            ))
+        })
+        .collect();
+    Ok(sinks)
+}
+
+fn json_index(value: &Value) -> Option<u32> {
+    value.as_u64().and_then(|i| u32::try_from(i).ok())
+}
+
+/// Average linear volume of an object with one entry per channel, e.g.
+/// `{"front-left": {"value": 65536, ...}, ...}`.
+fn json_volume(volume: &Value) -> Option<f64> {
+    let channels: Vec<f64> = volume
+        .as_object()?
+        .values()
+        .filter_map(channel_volume)
+        .collect();
+    average(&channels)
+}
+
+/// Linear volume of one channel, from the raw value or else the percentage.
+fn channel_volume(channel: &Value) -> Option<f64> {
+    #[allow(clippy::cast_precision_loss)]
+    let raw = channel["value"]
+        .as_u64()
+        .map(|value| value as f64 / VOLUME_NORM);
+    raw.or_else(|| parse_percent(channel["value_percent"].as_str()?))
+}
+
+/// Parse the text output of `pactl list sink-inputs` (in the C locale).
+fn parse_text(output: &str) -> HashMap<u32, AudioSinkInput> {
+    let mut sink_inputs = HashMap::new();
+    let mut current: Option<AudioSinkInput> = None;
+    // Per-channel volumes; lines with many channels wrap onto the next line
+    let mut channels: Vec<f64> = Vec::new();
+    let mut in_volume = false;
+
+    let mut finish = |sink_input: Option<AudioSinkInput>, channels: &mut Vec<f64>| {
+        if let Some(mut sink_input) = sink_input {
+            sink_input.volume = average(channels).unwrap_or(1.0);
+            sink_inputs.insert(sink_input.index, sink_input);
+        }
+        channels.clear();
+    };
+
+    for line in output.lines() {
+        let line = line.trim();
+
+        if let Some(index_str) = line.strip_prefix("Sink Input #") {
+            finish(current.take(), &mut channels);
+            in_volume = false;
+            current = index_str.parse().ok().map(|index| AudioSinkInput {
+                index,
+                application_name: String::new(),
+                volume: 1.0,
+                muted: false,
+                sink: u32::MAX,
+                process_id: None,
+                icon_name: None,
+            });
+            continue;
+        }
+        let Some(ref mut sink_input) = current else {
+            continue;
+        };
+
+        if let Some(volumes) = line.strip_prefix("Volume:") {
+            channels.extend(parse_volume_line(volumes));
+            in_volume = true;
+        } else if in_volume && line.contains('%') {
+            channels.extend(parse_volume_line(line));
+        } else {
+            in_volume = false;
+            if let Some(name) = line.strip_prefix("application.name = ") {
+                sink_input.application_name = name.trim_matches('"').to_string();
+            } else if let Some(pid) = line.strip_prefix("application.process.id = ") {
+                sink_input.process_id = pid.trim_matches('"').parse().ok();
+            } else if let Some(icon) = line.strip_prefix("application.icon_name = ") {
+                sink_input.icon_name = Some(icon.trim_matches('"').to_string());
+            } else if let Some(muted) = line.strip_prefix("Mute:") {
+                sink_input.muted = muted.trim() == "yes";
+            } else if let Some(sink) = line.strip_prefix("Sink:") {
+                sink_input.sink = sink.trim().parse().unwrap_or(u32::MAX);
+            }
+        }
+    }
+
+    finish(current, &mut channels);
+    sink_inputs
+}
+
+/// Parse the text output of `pactl list sinks` (in the C locale).
+fn parse_sinks_text(output: &str) -> HashMap<u32, AudioSink> {
+    let mut sinks = HashMap::new();
+    let mut current: Option<AudioSink> = None;
+    let mut channels: Vec<f64> = Vec::new();
+    let mut in_volume = false;
+
+    let mut finish = |sink: Option<AudioSink>, channels: &mut Vec<f64>| {
+        if let Some(mut sink) = sink.filter(|sink| !sink.name.is_empty()) {
+            if sink.description.is_empty() {
+                sink.description.clone_from(&sink.name);
+            }
+            sink.volume = average(channels).unwrap_or(1.0);
+            sinks.insert(sink.index, sink);
+        }
+        channels.clear();
+    };
+
+    for line in output.lines() {
+        let line = line.trim();
+
+        if let Some(index_str) = line.strip_prefix("Sink #") {
+            finish(current.take(), &mut channels);
+            in_volume = false;
+            current = index_str.parse().ok().map(|index| AudioSink {
+                index,
+                name: String::new(),
+                description: String::new(),
+                volume: 1.0,
+                muted: false,
+            });
+            continue;
+        }
+        let Some(ref mut sink) = current else {
+            continue;
+        };
+
+        if let Some(volumes) = line.strip_prefix("Volume:") {
+            channels.extend(parse_volume_line(volumes));
+            in_volume = true;
+        } else if in_volume && line.contains('%') {
+            channels.extend(parse_volume_line(line));
+        } else {
+            in_volume = false;
+            if let Some(name) = line.strip_prefix("Name: ") {
+                sink.name = name.to_string();
+            } else if let Some(description) = line.strip_prefix("Description: ") {
+                sink.description = description.to_string();
+            } else if let Some(muted) = line.strip_prefix("Mute:") {
+                sink.muted = muted.trim() == "yes";
+            }
+        }
+    }
+
+    finish(current, &mut channels);
+    sinks
+}
+
+/// Name of the default sink.
+pub fn default_sink() -> Result<Option<String>> {
+    Ok(parse_default_sink(&run_pactl(&["info"])?))
+}
+
+/// Read `Default Sink:` from the output of `pactl info` (in the C locale).
+fn parse_default_sink(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Default Sink:"))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Channel volumes of a line like
+/// `front-left: 65536 / 100% / 0.00 dB,   front-right: 52429 /  80% / -5.81 dB`.
+fn parse_volume_line(line: &str) -> impl Iterator<Item = f64> + '_ {
+    line.split(',')
+        .filter_map(|channel| channel.split('/').nth(1))
+        .filter_map(parse_percent)
+}
+
+/// `"80%"` → `0.8`.
+fn parse_percent(text: &str) -> Option<f64> {
+    let percent: f64 = text.trim().strip_suffix('%')?.trim().parse().ok()?;
+    Some(percent / 100.0)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn average(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+pub fn set_sink_input_volume(index: u32, volume: f64) -> Result<()> {
+    set_volume("set-sink-input-volume", index, volume)
+}
+
+pub fn set_sink_volume(index: u32, volume: f64) -> Result<()> {
+    set_volume("set-sink-volume", index, volume)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn set_volume(command: &str, index: u32, volume: f64) -> Result<()> {
+    // Clamp volume to 0.0-1.5 (150%)
+    let clamped_volume = volume.clamp(0.0, 1.5);
+
+    // Convert to percentage (safe: clamped range guarantees 0..=150)
+    let volume_percent = (clamped_volume * 100.0).round() as u32;
+
+    run_pactl(&[command, &index.to_string(), &format!("{volume_percent}%")])
+        .map_err(|_| anyhow::anyhow!("pactl {command} failed"))?;
+
+    Ok(())
+}
+
+pub fn set_sink_input_mute(index: u32, muted: bool) -> Result<()> {
+    run_pactl(&[
+        "set-sink-input-mute",
+        &index.to_string(),
+        if muted { "1" } else { "0" },
+    ])
+    .map_err(|_| anyhow::anyhow!("pactl set-sink-input-mute failed"))?;
+
+    Ok(())
+}
+
+pub fn set_sink_mute(index: u32, muted: bool) -> Result<()> {
+    run_pactl(&[
+        "set-sink-mute",
+        &index.to_string(),
+        if muted { "1" } else { "0" },
+    ])
+    .map_err(|_| anyhow::anyhow!("pactl set-sink-mute failed"))?;
+
+    Ok(())
+}
+
+pub fn set_default_sink(sink_name: &str) -> Result<()> {
+    run_pactl(&["set-default-sink", sink_name])
+        .map_err(|_| anyhow::anyhow!("pactl set-default-sink failed"))?;
+
+    Ok(())
+}
+
+pub fn move_sink_input(index: u32, sink_name: &str) -> Result<()> {
+    run_pactl(&["move-sink-input", &index.to_string(), sink_name])
+        .map_err(|_| anyhow::anyhow!("pactl move-sink-input failed"))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// pipewire-pulse 1.0, Firefox with an unbalanced stereo stream and a
+    /// muted mpv stream.
+    const PIPEWIRE_JSON: &str = r#"[{"index":42,"driver":"PipeWire","owner_module":"4294967295","client":"67","sink":55,"sample_specification":"float32le 2ch 48000Hz","channel_map":"front-left,front-right","format":"pcm, format.sample_format = \"\\\"float32le\\\"\"  format.rate = \"48000\"  format.channels = \"2\"  format.channel_map = \"\\\"front-left,front-right\\\"\"","corked":false,"mute":false,"volume":{"front-left":{"value":65536,"value_percent":"100%","db":"0.00 dB"},"front-right":{"value":52429,"value_percent":"80%","db":"-5.81 dB"}},"balance":-0.20,"buffer_latency_usec":0,"sink_latency_usec":0,"resample_method":"PipeWire","properties":{"client.api":"pipewire-pulse","pulse.server.type":"unix","application.name":"Firefox","application.process.id":"4242","application.process.binary":"firefox","application.icon_name":"firefox","media.name":"AudioStream"}},{"index":57,"driver":"PipeWire","owner_module":"4294967295","client":"71","sink":55,"sample_specification":"float32le 2ch 48000Hz","channel_map":"front-left,front-right","format":"pcm","corked":false,"mute":true,"volume":{"front-left":{"value":32768,"value_percent":"50%","db":"-18.06 dB"},"front-right":{"value":32768,"value_percent":"50%","db":"-18.06 dB"}},"balance":0.00,"buffer_latency_usec":0,"sink_latency_usec":0,"resample_method":"PipeWire","properties":{"application.name":"mpv Media Player","application.process.id":"5151","media.name":"Ünïcödé \"quoted\" title"}}]"#;
+
+    /// Stream without an `application.name` and only percentages.
+    const MINIMAL_JSON: &str =
+        r#"[{"index":3,"mute":false,"volume":{"mono":{"value_percent":"65%"}},"properties":{}}]"#;
+
+    /// `LC_ALL=C pactl list sink-inputs` on PulseAudio 16.
+    const PULSEAUDIO_TEXT: &str = r#"Sink Input #42
+	Driver: protocol-native.c
+	Owner Module: 9
+	Client: 67
+	Sink: 0
+	Sample Specification: s16le 2ch 44100Hz
+	Channel Map: front-left,front-right
+	Format: pcm, format.sample_format = "\"s16le\""  format.rate = "44100"  format.channels = "2"  format.channel_map = "\"front-left,front-right\""
+	Corked: no
+	Mute: no
+	Volume: front-left: 65536 / 100% / 0.00 dB,   front-right: 52429 /  80% / -5.81 dB
+	        balance -0.20
+	Buffer Latency: 23219 usec
+	Sink Latency: 35610 usec
+	Resample method: n/a
+	Properties:
+		media.name = "AudioStream"
+		application.name = "Firefox"
+		native-protocol.peer = "UNIX socket client"
+		application.process.id = "4242"
+		application.process.binary = "firefox"
+		application.icon_name = "firefox"
+
+Sink Input #57
+	Driver: protocol-native.c
+	Owner Module: 9
+	Client: 71
+	Sink: 0
+	Sample Specification: float32le 2ch 48000Hz
+	Channel Map: front-left,front-right
+	Format: pcm, format.sample_format = "\"float32le\""  format.rate = "48000"  format.channels = "2"  format.channel_map = "\"front-left,front-right\""
+	Corked: yes
+	Mute: yes
+	Volume: front-left: 32768 /  50% / -18.06 dB,   front-right: 32768 /  50% / -18.06 dB
+	        balance 0.00
+	Buffer Latency: 0 usec
+	Sink Latency: 0 usec
+	Resample method: n/a
+	Properties:
+		media.name = "audio stream"
+		application.name = "mpv Media Player"
+		application.process.id = "5151"
+"#;
+
+    /// 5.1 stream whose volume line wraps onto a second line.
+    const SURROUND_TEXT: &str = r#"Sink Input #8
+	Driver: PipeWire
+	Owner Module: n/a
+	Client: 12
+	Sink: 55
+	Sample Specification: float32le 6ch 48000Hz
+	Channel Map: front-left,front-right,rear-left,rear-right,front-center,lfe
+	Corked: no
+	Mute: no
+	Volume: front-left: 65536 / 100% / 0.00 dB,   front-right: 65536 / 100% / 0.00 dB,   rear-left: 32768 /  50% / -18.06 dB,
+	        rear-right: 32768 /  50% / -18.06 dB,   front-center: 65536 / 100% / 0.00 dB,   lfe: 65536 / 100% / 0.00 dB
+	        balance 0.00
+	Buffer Latency: 0 usec
+	Sink Latency: 0 usec
+	Resample method: PipeWire
+	Properties:
+		application.name = "VLC media player (LibVLC 3.0.20)"
+"#;
+
+    /// `pactl --format=json list sinks` on pipewire-pulse, trimmed to the
+    /// fields that are read; the HDMI sink has no description.
+    const SINKS_JSON: &str = r#"[{"index":55,"state":"RUNNING","name":"alsa_output.pci-0000_00_1f.3.analog-stereo","description":"Built-in Audio Analog Stereo","driver":"PipeWire","mute":false,"volume":{"front-left":{"value":78643,"value_percent":"120%","db":"4.75 dB"},"front-right":{"value":78643,"value_percent":"120%","db":"4.75 dB"}}},{"index":71,"state":"SUSPENDED","name":"alsa_output.pci-0000_01_00.1.hdmi-stereo","driver":"PipeWire","mute":true,"volume":{"front-left":{"value":32768,"value_percent":"50%","db":"-18.06 dB"},"front-right":{"value":32768,"value_percent":"50%","db":"-18.06 dB"}}}]"#;
+
+    /// `LC_ALL=C pactl list sinks` on PulseAudio 16, trimmed.
+    const SINKS_TEXT: &str = r#"Sink #0
+	State: RUNNING
+	Name: alsa_output.pci-0000_00_1f.3.analog-stereo
+	Description: Built-in Audio Analog Stereo
+	Driver: module-alsa-card.c
+	Mute: no
+	Volume: front-left: 65536 / 100% / 0.00 dB,   front-right: 52429 /  80% / -5.81 dB
+	        balance -0.20
+	Base Volume: 65536 / 100% / 0.00 dB
+
+Sink #3
+	State: SUSPENDED
+	Name: bluez_sink.00_1B_66_A1_B2_C3.a2dp_sink
+	Description: WH-1000XM4
+	Driver: module-bluez5-device.c
+	Mute: yes
+	Volume: front-left: 32768 /  50% / -18.06 dB,   front-right: 32768 /  50% / -18.06 dB
+	        balance 0.00
+"#;
+
+    /// `LC_ALL=C pactl info` on pipewire-pulse, trimmed.
+    const INFO_TEXT: &str = "Server String: /run/user/1000/pulse/native
+Server Name: PulseAudio (on PipeWire 1.0.5)
+Default Sink: alsa_output.pci-0000_00_1f.3.analog-stereo
+Default Source: alsa_input.pci-0000_00_1f.3.analog-stereo
+Cookie: 1a2b:3c4d
+";
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn json_averages_channels_and_reads_mute() {
+        let sink_inputs = parse_json(PIPEWIRE_JSON).unwrap();
+        assert_eq!(sink_inputs.len(), 2);
+
+        let firefox = &sink_inputs[&42];
+        assert_eq!(firefox.application_name, "Firefox");
+        assert_close(firefox.volume, 0.9);
+        assert!(!firefox.muted);
+        assert_eq!(firefox.sink, 55);
+        assert_eq!(firefox.process_id, Some(4242));
+        assert_eq!(firefox.icon_name.as_deref(), Some("firefox"));
+
+        let mpv = &sink_inputs[&57];
+        assert_eq!(mpv.application_name, "mpv Media Player");
+        assert_close(mpv.volume, 0.5);
+        assert!(mpv.muted);
+        assert_eq!(mpv.process_id, Some(5151));
+        assert_eq!(mpv.icon_name, None);
+    }
+
+    #[test]
+    fn json_falls_back_to_percentages() {
+        let sink_inputs = parse_json(MINIMAL_JSON).unwrap();
+        let stream = &sink_inputs[&3];
+        assert_eq!(stream.application_name, "");
+        assert_close(stream.volume, 0.65);
+        assert_eq!(stream.process_id, None);
+    }
+
+    #[test]
+    fn json_rejects_invalid_output() {
+        assert!(parse_json("Failed to connect: Connection refused").is_err());
+        assert!(parse_json("").is_err());
+    }
+
+    #[test]
+    fn json_empty_list() {
+        assert!(parse_json("[]").unwrap().is_empty());
+    }
+
+    #[test]
+    fn text_averages_channels_and_reads_mute() {
+        let sink_inputs = parse_text(PULSEAUDIO_TEXT);
+        assert_eq!(sink_inputs.len(), 2);
+
+        let firefox = &sink_inputs[&42];
+        assert_eq!(firefox.application_name, "Firefox");
+        assert_close(firefox.volume, 0.9);
+        assert!(!firefox.muted);
+        assert_eq!(firefox.sink, 0);
+        assert_eq!(firefox.process_id, Some(4242));
+        assert_eq!(firefox.icon_name.as_deref(), Some("firefox"));
+
+        let mpv = &sink_inputs[&57];
+        assert_eq!(mpv.application_name, "mpv Media Player");
+        assert_close(mpv.volume, 0.5);
+        assert!(mpv.muted);
+        assert_eq!(mpv.process_id, Some(5151));
+        assert_eq!(mpv.icon_name, None);
+    }
+
+    #[test]
+    fn text_reads_wrapped_volume_lines() {
+        let sink_inputs = parse_text(SURROUND_TEXT);
+        let vlc = &sink_inputs[&8];
+        assert_eq!(vlc.application_name, "VLC media player (LibVLC 3.0.20)");
+        assert_close(vlc.volume, 5.0 / 6.0);
+        assert_eq!(vlc.process_id, None);
+    }
+
+    #[test]
+    fn text_empty_output() {
+        assert!(parse_text("").is_empty());
+    }
+
+    #[test]
+    fn json_sinks_fall_back_to_name() {
+        let sinks = parse_sinks_json(SINKS_JSON).unwrap();
+        assert_eq!(sinks.len(), 2);
+        assert_eq!(
+            sinks[&55].name,
+            "alsa_output.pci-0000_00_1f.3.analog-stereo"
+        );
+        assert_eq!(sinks[&55].description, "Built-in Audio Analog Stereo");
+        assert_close(sinks[&55].volume, 1.2);
+        assert!(!sinks[&55].muted);
+        assert_eq!(
+            sinks[&71].description,
+            "alsa_output.pci-0000_01_00.1.hdmi-stereo"
+        );
+        assert!(sinks[&71].muted);
+    }
+
+    #[test]
+    fn text_sinks() {
+        let sinks = parse_sinks_text(SINKS_TEXT);
+        assert_eq!(sinks.len(), 2);
+        assert_eq!(sinks[&0].description, "Built-in Audio Analog Stereo");
+        assert_close(sinks[&0].volume, 0.9);
+        assert!(!sinks[&0].muted);
+        assert_eq!(sinks[&3].name, "bluez_sink.00_1B_66_A1_B2_C3.a2dp_sink");
+        assert_eq!(sinks[&3].description, "WH-1000XM4");
+        assert_close(sinks[&3].volume, 0.5);
+        assert!(sinks[&3].muted);
+    }
+
+    #[test]
+    fn text_default_sink() {
+        assert_eq!(
+            parse_default_sink(INFO_TEXT).as_deref(),
+            Some("alsa_output.pci-0000_00_1f.3.analog-stereo")
+        );
+        assert_eq!(parse_default_sink("Default Sink: \n"), None);
+        assert_eq!(parse_default_sink(""), None);
+    }
+}
@@ -0,0 +1,35 @@
+//! Process tree lookups through `/proc`.
+
+/// How many parent links to follow before giving up.
+const MAX_DEPTH: usize = 16;
+
+/// Whether `pid` is `ancestor` or one of its (grand)children.
+///
+/// Browsers play audio from helper processes, so a stream's process is often
+/// a descendant of the process owning the MPRIS name.
+pub fn is_same_or_descendant(pid: u32, ancestor: u32) -> bool {
+    let mut current = pid;
+    for _ in 0..MAX_DEPTH {
+        if current == ancestor {
+            return true;
+        }
+        match parent_pid(current) {
+            Some(parent) if parent > 1 => current = parent,
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Whether a process with this ID is still running.
+pub fn is_running(pid: u32) -> bool {
+    std::path::Path::new(&format!("/proc/{pid}")).exists()
+}
+
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The command name may contain spaces and parentheses; the fields after
+    // the last ')' are "state ppid ..."
+    let fields = &stat[stat.rfind(')')? + 1..];
+    fields.split_whitespace().nth(1)?.parse().ok()
+}
@@ -0,0 +1,602 @@
+//! Native PulseAudio backend (also serves PipeWire through pipewire-pulse).
+//!
+//! libpulse objects are not `Send`, so a dedicated thread owns the mainloop and
+//! context. It keeps the shared sink and sink input tables and the default sink
+//! current from subscription events and applies volume, mute, move and
+//! default sink commands sent over a channel. While asked to, it also records
+//! sink inputs from their sink's monitor source to keep their levels current.
+//! If the server goes away, the thread reconnects.
+
+use super::spectrum::FFT_SIZE;
+use super::{AudioLevel, AudioSink, AudioSinkInput, MONITOR_RATE};
+use libpulse_binding as pulse;
+use pulse::callbacks::ListResult;
+use pulse::context::introspect::{ServerInfo, SinkInfo, SinkInputInfo};
+use pulse::context::subscribe::{Facility, InterestMaskSet, Operation as SubscribeOperation};
+use pulse::context::{Context, FlagSet as ContextFlagSet, State as ContextState};
+use pulse::def::BufferAttr;
+use pulse::mainloop::standard::{IterateResult, Mainloop};
+use pulse::proplist::{properties, Proplist};
+use pulse::sample::{Format, Spec};
+use pulse::stream::{FlagSet as StreamFlagSet, PeekResult, State as StreamState, Stream};
+use pulse::volume::{ChannelVolumes, Volume};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// How long the first connection may take before falling back to `pactl`.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Delay between reconnection attempts after the server went away.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Longest a queued command waits while the mainloop is idle.
+const POLL_INTERVAL: pulse::time::MicroSeconds = pulse::time::MicroSeconds(50_000);
+
+/// Bytes per monitored fragment (mono floats): a level update about 30 times
+/// a second.
+const MONITOR_FRAGMENT_BYTES: u32 = MONITOR_RATE / 30 * 4;
+
+pub type SinkInputTable = Arc<Mutex<HashMap<u32, AudioSinkInput>>>;
+pub type SinkTable = Arc<Mutex<HashMap<u32, AudioSink>>>;
+/// Name of the server's default sink
+pub type DefaultSink = Arc<Mutex<Option<String>>>;
+/// Latest level per monitored sink input
+pub type LevelTable = Arc<Mutex<HashMap<u32, AudioLevel>>>;
+/// The last [`FFT_SIZE`] samples per monitored sink input
+pub type SampleTable = Arc<Mutex<HashMap<u32, VecDeque<f32>>>>;
+
+/// Shared state the backend keeps current.
+pub struct Tables {
+    pub sink_inputs: SinkInputTable,
+    pub sinks: SinkTable,
+    pub default_sink: DefaultSink,
+    pub levels: LevelTable,
+    pub samples: SampleTable,
+}
+
+enum Command {
+    SetVolume(u32, f64),
+    SetMute(u32, bool),
+    /// Move a sink input to the sink with this name
+    Move(u32, String),
+    SetSinkVolume(u32, f64),
+    SetSinkMute(u32, bool),
+    SetDefaultSink(String),
+    /// Monitor exactly these sink inputs
+    Monitor(Vec<u32>),
+}
+
+/// Handle to the PulseAudio thread; dropping it stops the thread.
+pub struct PulseBackend {
+    commands: Sender<Command>,
+    /// Set when the first connection failed and the thread gave up
+    failed: Arc<AtomicBool>,
+}
+
+impl PulseBackend {
+    /// Start the PulseAudio thread, which connects in the background.
+    /// Commands sent meanwhile are applied once connected.
+    ///
+    /// `changes` is notified once the first connection succeeded or failed,
+    /// and whenever one of the `tables` changes.
+    pub fn spawn(tables: Tables, changes: Arc<Notify>) -> anyhow::Result<Self> {
+        let (commands, command_rx) = mpsc::channel();
+        let failed = Arc::new(AtomicBool::new(false));
+
+        let thread_failed = Arc::clone(&failed);
+        std::thread::Builder::new()
+            .name("pulseaudio".to_string())
+            .spawn(move || run(&tables, &changes, &command_rx, &thread_failed))?;
+
+        Ok(Self { commands, failed })
+    }
+
+    /// Whether the server could not be reached at startup; `pactl` is used
+    /// instead then.
+    pub fn failed(&self) -> bool {
+        self.failed.load(Ordering::Acquire)
+    }
+
+    pub fn set_volume(&self, index: u32, volume: f64) -> anyhow::Result<()> {
+        self.send(Command::SetVolume(index, volume))
+    }
+
+    pub fn set_mute(&self, index: u32, muted: bool) -> anyhow::Result<()> {
+        self.send(Command::SetMute(index, muted))
+    }
+
+    pub fn move_sink_input(&self, index: u32, sink_name: &str) -> anyhow::Result<()> {
+        self.send(Command::Move(index, sink_name.to_string()))
+    }
+
+    pub fn set_sink_volume(&self, index: u32, volume: f64) -> anyhow::Result<()> {
+        self.send(Command::SetSinkVolume(index, volume))
+    }
+
+    pub fn set_sink_mute(&self, index: u32, muted: bool) -> anyhow::Result<()> {
+        self.send(Command::SetSinkMute(index, muted))
+    }
+
+    pub fn set_default_sink(&self, sink_name: &str) -> anyhow::Result<()> {
+        self.send(Command::SetDefaultSink(sink_name.to_string()))
+    }
+
+    /// Keep the levels of these sink inputs current, and stop monitoring any
+    /// others. An empty list stops monitoring altogether.
+    pub fn monitor(&self, indices: &[u32]) -> anyhow::Result<()> {
+        self.send(Command::Monitor(indices.to_vec()))
+    }
+
+    fn send(&self, command: Command) -> anyhow::Result<()> {
+        self.commands
+            .send(command)
+            .map_err(|_| anyhow::anyhow!("PulseAudio thread has stopped"))
+    }
+}
+
+/// Thread body: connect, serve until the connection drops, reconnect.
+fn run(tables: &Tables, changes: &Arc<Notify>, commands: &Receiver<Command>, failed: &AtomicBool) {
+    let mut connected = false;
+    loop {
+        match Connection::open(tables, changes) {
+            Ok(mut connection) => {
+                if !connected {
+                    connected = true;
+                    changes.notify_one();
+                }
+                if connection.serve(commands) == Served::Stopped {
+                    return;
+                }
+                eprintln!("Lost PulseAudio connection, reconnecting");
+            }
+            // Without a first connection, the controller falls back to pactl
+            Err(e) if !connected => {
+                eprintln!("PulseAudio connection failed: {e}; falling back to pactl");
+                failed.store(true, Ordering::Release);
+                changes.notify_one();
+                return;
+            }
+            Err(e) => eprintln!("PulseAudio reconnect failed: {e}"),
+        }
+
+        // Streams and devices are unknown while disconnected
+        lock(&tables.sink_inputs).clear();
+        lock(&tables.sinks).clear();
+        *lock(&tables.default_sink) = None;
+        lock(&tables.levels).clear();
+        lock(&tables.samples).clear();
+        changes.notify_one();
+        std::thread::sleep(RECONNECT_DELAY);
+    }
+}
+
+#[derive(PartialEq, Eq)]
+enum Served {
+    /// The controller was dropped
+    Stopped,
+    /// The server went away
+    Disconnected,
+}
+
+struct Connection {
+    /// Level monitoring streams by sink input index. Declared first, with
+    /// the context, so both are dropped before the mainloop
+    monitors: HashMap<u32, Rc<RefCell<Stream>>>,
+    context: Context,
+    mainloop: Mainloop,
+    sink_inputs: SinkInputTable,
+    sinks: SinkTable,
+    default_sink: DefaultSink,
+    levels: LevelTable,
+    samples: SampleTable,
+    changes: Arc<Notify>,
+    /// Monitor source name per sink index
+    monitor_sources: Rc<RefCell<HashMap<u32, String>>>,
+    /// Per-channel volumes of sink inputs and sinks, to keep the balance
+    /// when setting a volume
+    channel_volumes: Rc<RefCell<HashMap<u32, ChannelVolumes>>>,
+    sink_channel_volumes: Rc<RefCell<HashMap<u32, ChannelVolumes>>>,
+    /// Server, sink and sink input events received since the last pass of
+    /// the loop
+    events: Rc<RefCell<Vec<(Facility, SubscribeOperation, u32)>>>,
+}
+
+impl Connection {
+    fn open(tables: &Tables, changes: &Arc<Notify>) -> Result<Self, String> {
+        let mut mainloop = Mainloop::new().ok_or("failed to create mainloop")?;
+
+        let mut proplist = Proplist::new().ok_or("failed to create proplist")?;
+        let _ = proplist.set_str(properties::APPLICATION_NAME, "COSMIC Music Player");
+        let _ = proplist.set_str(properties::APPLICATION_ID, "com.github.MusicPlayer");
+
+        let mut context =
+            Context::new_with_proplist(&mainloop, "cosmic-applet-music-player", &proplist)
+                .ok_or("failed to create context")?;
+        context
+            .connect(None, ContextFlagSet::NOFLAGS, None)
+            .map_err(|e| format!("{e}"))?;
+
+        let deadline = Instant::now() + CONNECT_TIMEOUT;
+        loop {
+            if Instant::now() > deadline {
+                return Err("connection timed out".to_string());
+            }
+            match mainloop.iterate(true) {
+                IterateResult::Success(_) => {}
+                IterateResult::Quit(_) | IterateResult::Err(_) => {
+                    return Err("mainloop failed while connecting".to_string());
+                }
+            }
+            match context.get_state() {
+                ContextState::Ready => break,
+                ContextState::Failed | ContextState::Terminated => {
+                    return Err("server refused the connection".to_string());
+                }
+                _ => {}
+            }
+        }
+
+        let mut connection = Self {
+            monitors: HashMap::new(),
+            context,
+            mainloop,
+            sink_inputs: Arc::clone(&tables.sink_inputs),
+            sinks: Arc::clone(&tables.sinks),
+            default_sink: Arc::clone(&tables.default_sink),
+            levels: Arc::clone(&tables.levels),
+            samples: Arc::clone(&tables.samples),
+            changes: Arc::clone(changes),
+            monitor_sources: Rc::new(RefCell::new(HashMap::new())),
+            channel_volumes: Rc::new(RefCell::new(HashMap::new())),
+            sink_channel_volumes: Rc::new(RefCell::new(HashMap::new())),
+            events: Rc::new(RefCell::new(Vec::new())),
+        };
+        connection.subscribe();
+        Ok(connection)
+    }
+
+    /// Subscribe to server, sink and sink input events and load the current
+    /// state.
+    fn subscribe(&mut self) {
+        let events = Rc::clone(&self.events);
+        self.context.set_subscribe_callback(Some(Box::new(
+            move |facility: Option<Facility>, operation: Option<SubscribeOperation>, index: u32| {
+                if let (
+                    Some(facility @ (Facility::Server | Facility::Sink | Facility::SinkInput)),
+                    Some(operation),
+                ) = (facility, operation)
+                {
+                    events.borrow_mut().push((facility, operation, index));
+                }
+            },
+        )));
+        let _ = self.context.subscribe(
+            InterestMaskSet::SERVER | InterestMaskSet::SINK | InterestMaskSet::SINK_INPUT,
+            |_| {},
+        );
+
+        lock(&self.sink_inputs).clear();
+        lock(&self.sinks).clear();
+        self.channel_volumes.borrow_mut().clear();
+        self.sink_channel_volumes.borrow_mut().clear();
+        self.monitor_sources.borrow_mut().clear();
+        let introspect = self.context.introspect();
+        let _ = introspect.get_sink_input_info_list(self.info_callback());
+        let _ = introspect.get_sink_info_list(self.sink_callback());
+        let _ = introspect.get_server_info(self.server_callback());
+    }
+
+    /// Callback storing introspected sink inputs in the shared table.
+    fn info_callback(&self) -> impl FnMut(ListResult<&SinkInputInfo>) + 'static {
+        let sink_inputs = Arc::clone(&self.sink_inputs);
+        let channel_volumes = Rc::clone(&self.channel_volumes);
+        let changes = Arc::clone(&self.changes);
+        move |result| {
+            if let ListResult::Item(info) = result {
+                channel_volumes.borrow_mut().insert(info.index, info.volume);
+                lock(&sink_inputs).insert(info.index, sink_input_from_info(info));
+                changes.notify_one();
+            }
+        }
+    }
+
+    /// Callback storing introspected sinks in the shared table.
+    fn sink_callback(&self) -> impl FnMut(ListResult<&SinkInfo>) + 'static {
+        let sinks = Arc::clone(&self.sinks);
+        let channel_volumes = Rc::clone(&self.sink_channel_volumes);
+        let monitor_sources = Rc::clone(&self.monitor_sources);
+        let changes = Arc::clone(&self.changes);
+        move |result| {
+            if let ListResult::Item(info) = result {
+                channel_volumes.borrow_mut().insert(info.index, info.volume);
+                if let Some(ref source) = info.monitor_source_name {
+                    monitor_sources
+                        .borrow_mut()
+                        .insert(info.index, source.to_string());
+                }
+                if let Some(sink) = sink_from_info(info) {
+                    lock(&sinks).insert(sink.index, sink);
+                    changes.notify_one();
+                }
+            }
+        }
+    }
+
+    /// Callback storing the default sink name.
+    fn server_callback(&self) -> impl FnMut(&ServerInfo) + 'static {
+        let default_sink = Arc::clone(&self.default_sink);
+        let changes = Arc::clone(&self.changes);
+        move |info| {
+            *lock(&default_sink) = info.default_sink_name.as_deref().map(ToString::to_string);
+            changes.notify_one();
+        }
+    }
+
+    /// Run until the connection drops or the controller is dropped.
+    fn serve(&mut self, commands: &Receiver<Command>) -> Served {
+        loop {
+            loop {
+                match commands.try_recv() {
+                    Ok(command) => self.apply(command),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return Served::Stopped,
+                }
+            }
+
+            let events: Vec<_> = self.events.borrow_mut().drain(..).collect();
+            for (facility, operation, index) in events {
+                self.handle_event(facility, operation, index);
+            }
+
+            // Wait for server events, but wake up regularly for commands
+            let iterated = self
+                .mainloop
+                .prepare(Some(POLL_INTERVAL))
+                .and_then(|()| self.mainloop.poll())
+                .and_then(|_| self.mainloop.dispatch());
+            if iterated.is_err() || self.context.get_state() != ContextState::Ready {
+                return Served::Disconnected;
+            }
+        }
+    }
+
+    fn handle_event(&mut self, facility: Facility, operation: SubscribeOperation, index: u32) {
+        let introspect = self.context.introspect();
+        match (facility, operation) {
+            (Facility::SinkInput, SubscribeOperation::New | SubscribeOperation::Changed) => {
+                let _ = introspect.get_sink_input_info(index, self.info_callback());
+            }
+            (Facility::SinkInput, SubscribeOperation::Removed) => {
+                self.channel_volumes.borrow_mut().remove(&index);
+                self.monitors.remove(&index);
+                lock(&self.levels).remove(&index);
+                lock(&self.samples).remove(&index);
+                lock(&self.sink_inputs).remove(&index);
+                self.changes.notify_one();
+            }
+            (Facility::Sink, SubscribeOperation::New | SubscribeOperation::Changed) => {
+                let _ = introspect.get_sink_info_by_index(index, self.sink_callback());
+            }
+            (Facility::Sink, SubscribeOperation::Removed) => {
+                self.sink_channel_volumes.borrow_mut().remove(&index);
+                self.monitor_sources.borrow_mut().remove(&index);
+                lock(&self.sinks).remove(&index);
+                self.changes.notify_one();
+            }
+            // The default sink may have changed
+            _ => {
+                let _ = introspect.get_server_info(self.server_callback());
+            }
+        }
+    }
+
+    fn apply(&mut self, command: Command) {
+        let mut introspect = self.context.introspect();
+        match command {
+            Command::SetVolume(index, volume) => {
+                let Some(volumes) = self.channel_volumes.borrow().get(&index).copied() else {
+                    eprintln!("Unknown sink input {index}");
+                    return;
+                };
+                let _ = introspect.set_sink_input_volume(index, &scaled(volumes, volume), None);
+            }
+            Command::SetMute(index, muted) => {
+                let _ = introspect.set_sink_input_mute(index, muted, None);
+            }
+            Command::Move(index, sink_name) => {
+                let _ = introspect.move_sink_input_by_name(index, &sink_name, None);
+            }
+            Command::SetSinkVolume(index, volume) => {
+                let Some(volumes) = self.sink_channel_volumes.borrow().get(&index).copied() else {
+                    eprintln!("Unknown sink {index}");
+                    return;
+                };
+                let _ = introspect.set_sink_volume_by_index(index, &scaled(volumes, volume), None);
+            }
+            Command::SetSinkMute(index, muted) => {
+                let _ = introspect.set_sink_mute_by_index(index, muted, None);
+            }
+            Command::SetDefaultSink(sink_name) => {
+                let _ = self.context.set_default_sink(&sink_name, |_| {});
+            }
+            Command::Monitor(indices) => self.set_monitors(&indices),
+        }
+    }
+
+    /// Monitor exactly `indices`. Streams the server ended, e.g. because
+    /// the sink input moved to another sink, are started again.
+    fn set_monitors(&mut self, indices: &[u32]) {
+        self.monitors.retain(|index, stream| {
+            let mut stream = stream.borrow_mut();
+            let alive = !matches!(
+                stream.get_state(),
+                StreamState::Failed | StreamState::Terminated
+            );
+            let keep = alive && indices.contains(index);
+            if alive && !keep {
+                let _ = stream.disconnect();
+            }
+            keep
+        });
+        lock(&self.levels).retain(|index, _| self.monitors.contains_key(index));
+        lock(&self.samples).retain(|index, _| self.monitors.contains_key(index));
+
+        for &index in indices {
+            if self.monitors.contains_key(&index) {
+                continue;
+            }
+            match self.monitor(index) {
+                Ok(stream) => {
+                    self.monitors.insert(index, stream);
+                }
+                Err(e) => eprintln!("Failed to monitor audio stream #{index}: {e}"),
+            }
+        }
+    }
+
+    /// Record a sink input from its sink's monitor source, storing the level
+    /// of every fragment and the latest samples.
+    fn monitor(&mut self, index: u32) -> Result<Rc<RefCell<Stream>>, String> {
+        let sink = lock(&self.sink_inputs)
+            .get(&index)
+            .map(|sink_input| sink_input.sink)
+            .ok_or("unknown sink input")?;
+        let source = self
+            .monitor_sources
+            .borrow()
+            .get(&sink)
+            .cloned()
+            .ok_or("sink has no monitor source")?;
+
+        let spec = Spec {
+            format: Format::FLOAT32NE,
+            channels: 1,
+            rate: MONITOR_RATE,
+        };
+        let mut stream = Stream::new(&mut self.context, "Level meter", &spec, None)
+            .ok_or("failed to create stream")?;
+        stream
+            .set_monitor_stream(index)
+            .map_err(|e| format!("{e}"))?;
+        let attr = BufferAttr {
+            maxlength: u32::MAX,
+            tlength: u32::MAX,
+            prebuf: u32::MAX,
+            minreq: u32::MAX,
+            fragsize: MONITOR_FRAGMENT_BYTES,
+        };
+        stream
+            .connect_record(
+                Some(&source),
+                Some(&attr),
+                StreamFlagSet::DONT_MOVE
+                    | StreamFlagSet::ADJUST_LATENCY
+                    | StreamFlagSet::DONT_INHIBIT_AUTO_SUSPEND,
+            )
+            .map_err(|e| format!("{e}"))?;
+
+        let stream = Rc::new(RefCell::new(stream));
+        let weak = Rc::downgrade(&stream);
+        let levels = Arc::clone(&self.levels);
+        let samples = Arc::clone(&self.samples);
+        stream
+            .borrow_mut()
+            .set_read_callback(Some(Box::new(move |_| {
+                let Some(stream) = weak.upgrade() else {
+                    return;
+                };
+                let mut stream = stream.borrow_mut();
+                loop {
+                    let fragment: Option<Vec<f32>> = match stream.peek() {
+                        Ok(PeekResult::Data(data)) => Some(
+                            data.chunks_exact(4)
+                                .map(|bytes| {
+                                    f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+                                })
+                                .collect(),
+                        ),
+                        Ok(PeekResult::Hole(_)) => None,
+                        Ok(PeekResult::Empty) | Err(_) => break,
+                    };
+                    let _ = stream.discard();
+                    let Some(fragment) = fragment else {
+                        continue;
+                    };
+
+                    lock(&levels).insert(index, AudioLevel::from_samples(fragment.iter().copied()));
+                    let mut histories = lock(&samples);
+                    let history = histories.entry(index).or_default();
+                    history.extend(fragment);
+                    let excess = history.len().saturating_sub(FFT_SIZE);
+                    history.drain(..excess);
+                }
+            })));
+        Ok(stream)
+    }
+}
+
+/// Scale all channels so their average hits `volume` (linear, clamped to
+/// 0.0-1.5), keeping the balance between them.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn scaled(mut volumes: ChannelVolumes, volume: f64) -> ChannelVolumes {
+    let target = volume.clamp(0.0, 1.5) * f64::from(Volume::NORMAL.0);
+    let average = f64::from(volumes.avg().0);
+    for channel in volumes.get_mut() {
+        let scaled = if average > 0.0 {
+            f64::from(channel.0) * target / average
+        } else {
+            target
+        };
+        channel.0 = scaled.round().clamp(0.0, f64::from(Volume::MAX.0)) as u32;
+    }
+    volumes
+}
+
+/// Linear volume (1.0 = 100%, as shown by pactl) averaged over channels.
+fn sink_input_from_info(info: &SinkInputInfo) -> AudioSinkInput {
+    AudioSinkInput {
+        index: info.index,
+        application_name: info
+            .proplist
+            .get_str(properties::APPLICATION_NAME)
+            .unwrap_or_default(),
+        volume: f64::from(info.volume.avg().0) / f64::from(Volume::NORMAL.0),
+        muted: info.mute,
+        sink: info.sink,
+        process_id: info
+            .proplist
+            .get_str(properties::APPLICATION_PROCESS_ID)
+            .and_then(|pid| pid.parse().ok()),
+        icon_name: info.proplist.get_str(properties::APPLICATION_ICON_NAME),
+    }
+}
+
+fn sink_from_info(info: &SinkInfo) -> Option<AudioSink> {
+    let name = info.name.as_deref()?.to_string();
+    Some(AudioSink {
+        index: info.index,
+        volume: f64::from(info.volume.avg().0) / f64::from(Volume::NORMAL.0),
+        muted: info.mute,
+        description: info
+            .description
+            .as_deref()
+            .map_or_else(|| name.clone(), ToString::to_string),
+        name,
+    })
+}
+
+fn lock<T>(table: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    table
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
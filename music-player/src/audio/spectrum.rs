@@ -0,0 +1,241 @@
+//! Bar spectrum of a monitored stream for the visualizer.
+//!
+//! The latest [`FFT_SIZE`] samples are windowed and transformed, and the
+//! magnitudes are grouped into bars spaced logarithmically from
+//! [`MIN_FREQUENCY`] up to the Nyquist frequency, like most equalizer
+//! displays.
+
+use super::MONITOR_RATE;
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+use std::sync::Arc;
+
+/// Samples per analysis window (about 93 ms at [`MONITOR_RATE`]).
+pub const FFT_SIZE: usize = 2048;
+
+/// Lowest frequency shown, in Hz.
+const MIN_FREQUENCY: f32 = 40.0;
+
+/// Magnitudes this far below full scale draw as empty bars.
+const RANGE_DB: f32 = 70.0;
+
+pub struct Spectrum {
+    fft: Arc<dyn Fft<f32>>,
+    /// Hann window, and its sum to normalize magnitudes
+    window: Vec<f32>,
+    window_sum: f32,
+    /// Smoothed bar heights, 0.0-1.0
+    bars: Vec<f32>,
+}
+
+impl Default for Spectrum {
+    #[allow(clippy::cast_precision_loss)]
+    fn default() -> Self {
+        let window: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| {
+                let phase = std::f32::consts::TAU * i as f32 / (FFT_SIZE - 1) as f32;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+        Self {
+            fft: FftPlanner::new().plan_fft_forward(FFT_SIZE),
+            window_sum: window.iter().sum(),
+            window,
+            bars: Vec::new(),
+        }
+    }
+}
+
+impl Spectrum {
+    pub fn bars(&self) -> &[f32] {
+        &self.bars
+    }
+
+    pub fn clear(&mut self) {
+        self.bars.clear();
+    }
+
+    /// Analyze the latest window of `samples` into `bar_count` bars. Bars
+    /// rise at once and fall back by `smoothing` (0.0 = no smoothing, close
+    /// to 1.0 = slow decay) per update.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn update(&mut self, samples: &[f32], bar_count: usize, smoothing: f32) {
+        // Oldest samples first; a short history is padded with silence
+        let samples = &samples[samples.len().saturating_sub(FFT_SIZE)..];
+        let padding = FFT_SIZE - samples.len();
+        let mut buffer: Vec<Complex<f32>> = std::iter::repeat(0.0)
+            .take(padding)
+            .chain(samples.iter().copied())
+            .zip(&self.window)
+            .map(|(sample, weight)| Complex::new(sample * weight, 0.0))
+            .collect();
+        self.fft.process(&mut buffer);
+
+        // Amplitude relative to a full scale sine
+        let magnitudes: Vec<f32> = buffer[..FFT_SIZE / 2]
+            .iter()
+            .map(|bin| 2.0 * bin.norm() / self.window_sum)
+            .collect();
+
+        let nyquist = MONITOR_RATE as f32 / 2.0;
+        let bin_of = |bar: usize| {
+            let frequency =
+                MIN_FREQUENCY * (nyquist / MIN_FREQUENCY).powf(bar as f32 / bar_count as f32);
+            frequency * FFT_SIZE as f32 / MONITOR_RATE as f32
+        };
+
+        if self.bars.len() != bar_count {
+            self.bars = vec![0.0; bar_count];
+        }
+        for (bar, height) in self.bars.iter_mut().enumerate() {
+            let low = (bin_of(bar).floor() as usize).clamp(1, magnitudes.len() - 1);
+            let high = (bin_of(bar + 1).ceil() as usize).clamp(low + 1, magnitudes.len());
+            let magnitude = magnitudes[low..high].iter().copied().fold(0.0, f32::max);
+
+            let db = 20.0 * magnitude.max(f32::MIN_POSITIVE).log10();
+            let target = ((db + RANGE_DB) / RANGE_DB).clamp(0.0, 1.0);
+            *height = if target >= *height {
+                target
+            } else {
+                *height * smoothing + target * (1.0 - smoothing)
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `len` samples of a full scale sine at `frequency` Hz.
+    #[allow(clippy::cast_precision_loss)]
+    fn sine(frequency: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / MONITOR_RATE as f32;
+                (std::f32::consts::TAU * frequency * t).sin()
+            })
+            .collect()
+    }
+
+    /// The bar whose frequency range holds `frequency`.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn expected_bar(frequency: f32, bar_count: usize) -> usize {
+        let nyquist = MONITOR_RATE as f32 / 2.0;
+        let position = (frequency / MIN_FREQUENCY).ln() / (nyquist / MIN_FREQUENCY).ln();
+        (position * bar_count as f32) as usize
+    }
+
+    /// Whether `bar` is the highest, sharing the top with neighbors that
+    /// span the same FFT bins at low frequencies.
+    fn is_peak(bars: &[f32], bar: usize) -> bool {
+        bars.iter().all(|height| *height <= bars[bar])
+    }
+
+    #[test]
+    fn sine_peaks_in_its_bar() {
+        for (frequency, bar_count) in [
+            (110.0, 16),
+            (440.0, 32),
+            (1_000.0, 32),
+            (3_000.0, 24),
+            (8_000.0, 32),
+        ] {
+            let mut spectrum = Spectrum::default();
+            spectrum.update(&sine(frequency, FFT_SIZE), bar_count, 0.0);
+            let bars = spectrum.bars();
+            let expected = expected_bar(frequency, bar_count);
+
+            assert_eq!(bars.len(), bar_count);
+            assert!(is_peak(bars, expected), "{frequency} Hz in {bars:?}");
+            assert!(bars[expected] > 0.95, "{frequency} Hz in {bars:?}");
+            // Far away bars only see window leakage
+            for (bar, height) in bars.iter().enumerate() {
+                if bar.abs_diff(expected) > 3 {
+                    assert!(*height < 0.5, "{frequency} Hz in {bars:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn silence_draws_empty_bars() {
+        let mut spectrum = Spectrum::default();
+        spectrum.update(&[0.0; FFT_SIZE], 32, 0.0);
+        assert_eq!(spectrum.bars(), [0.0; 32]);
+
+        spectrum.update(&[], 32, 0.0);
+        assert_eq!(spectrum.bars(), [0.0; 32]);
+    }
+
+    #[test]
+    fn single_bar_covers_the_whole_range() {
+        for frequency in [100.0, 1_000.0, 10_000.0] {
+            let mut spectrum = Spectrum::default();
+            spectrum.update(&sine(frequency, FFT_SIZE), 1, 0.0);
+            assert_eq!(spectrum.bars().len(), 1);
+            assert!(spectrum.bars()[0] > 0.95, "{frequency} Hz");
+        }
+    }
+
+    #[test]
+    fn many_bars_stay_in_range() {
+        let mut spectrum = Spectrum::default();
+        spectrum.update(&sine(2_000.0, FFT_SIZE), 64, 0.0);
+        let bars = spectrum.bars();
+        assert_eq!(bars.len(), 64);
+        assert!(is_peak(bars, expected_bar(2_000.0, 64)));
+        assert!(bars.iter().all(|height| (0.0..=1.0).contains(height)));
+    }
+
+    #[test]
+    fn bar_count_changes_resize_the_bars() {
+        let mut spectrum = Spectrum::default();
+        spectrum.update(&sine(440.0, FFT_SIZE), 64, 0.5);
+        spectrum.update(&sine(440.0, FFT_SIZE), 1, 0.5);
+        assert_eq!(spectrum.bars().len(), 1);
+        spectrum.clear();
+        assert!(spectrum.bars().is_empty());
+    }
+
+    #[test]
+    fn short_history_is_padded() {
+        let mut spectrum = Spectrum::default();
+        spectrum.update(&sine(1_000.0, 256), 32, 0.0);
+        let bars = spectrum.bars();
+        assert_eq!(bars.len(), 32);
+        assert!(bars.iter().all(|height| (0.0..=1.0).contains(height)));
+        assert!(is_peak(bars, expected_bar(1_000.0, 32)));
+
+        // Only the latest window counts when the history is longer
+        let mut samples = sine(1_000.0, FFT_SIZE);
+        samples.extend(std::iter::repeat(0.0).take(FFT_SIZE));
+        spectrum.update(&samples, 32, 0.0);
+        assert_eq!(spectrum.bars(), [0.0; 32]);
+    }
+
+    #[test]
+    fn bars_rise_at_once_and_decay_with_smoothing() {
+        let mut spectrum = Spectrum::default();
+        let tone = sine(1_000.0, FFT_SIZE);
+        let bar = expected_bar(1_000.0, 32);
+
+        spectrum.update(&tone, 32, 0.5);
+        let loud = spectrum.bars()[bar];
+        assert!(loud > 0.95);
+
+        spectrum.update(&[0.0; FFT_SIZE], 32, 0.5);
+        assert!((spectrum.bars()[bar] - loud * 0.5).abs() < 1e-6);
+
+        spectrum.update(&tone, 32, 0.5);
+        assert!((spectrum.bars()[bar] - loud).abs() < 1e-6);
+    }
+}
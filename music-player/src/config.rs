@@ -1,16 +1,374 @@
+use crate::art::fetch::{FetchPolicy, DEFAULT_USER_AGENT};
+use crate::art::paths::{self, PathPolicy};
+use chrono::{NaiveTime, Timelike};
 use cosmic::cosmic_config::{Config, ConfigGet, ConfigSet};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 const CONFIG_VERSION: u64 = 1;
 
+pub const DEFAULT_ART_CACHE_MAX_MB: u32 = 100;
+pub const DEFAULT_MUSICBRAINZ_URL: &str = "https://musicbrainz.org";
+pub const DEFAULT_COVER_ART_ARCHIVE_URL: &str = "https://coverartarchive.org";
+
+/// Action triggered by a click gesture on the panel icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PanelAction {
+    None,
+    TogglePopup,
+    PlayPause,
+    Next,
+    Previous,
+    VolumeUp,
+    VolumeDown,
+    SeekForward,
+    SeekBackward,
+    CyclePlayer,
+    CyclePlayerBack,
+    ContextMenu,
+}
+
+impl PanelAction {
+    /// Actions offered for click gestures, in Settings dropdown order.
+    pub const ALL: [PanelAction; 12] = [
+        PanelAction::None,
+        PanelAction::TogglePopup,
+        PanelAction::ContextMenu,
+        PanelAction::PlayPause,
+        PanelAction::Next,
+        PanelAction::Previous,
+        PanelAction::VolumeUp,
+        PanelAction::VolumeDown,
+        PanelAction::SeekForward,
+        PanelAction::SeekBackward,
+        PanelAction::CyclePlayer,
+        PanelAction::CyclePlayerBack,
+    ];
+
+    pub const LABELS: [&'static str; 12] = [
+        "Nothing",
+        "Toggle popup",
+        "Quick actions menu",
+        "Play/pause",
+        "Next track",
+        "Previous track",
+        "Volume up",
+        "Volume down",
+        "Seek forward",
+        "Seek backward",
+        "Next player",
+        "Previous player",
+    ];
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|a| *a == self).unwrap_or(0)
+    }
+}
+
+/// Action triggered by a scroll gesture on the panel icon. Scrolling up (or
+/// right) maps to the "forward" direction of the action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScrollAction {
+    None,
+    Track,
+    Volume,
+    Seek,
+    CyclePlayer,
+}
+
+impl ScrollAction {
+    pub const ALL: [ScrollAction; 5] = [
+        ScrollAction::None,
+        ScrollAction::Track,
+        ScrollAction::Volume,
+        ScrollAction::Seek,
+        ScrollAction::CyclePlayer,
+    ];
+
+    pub const LABELS: [&'static str; 5] = [
+        "Nothing",
+        "Change track",
+        "Change volume",
+        "Seek",
+        "Switch player",
+    ];
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|a| *a == self).unwrap_or(0)
+    }
+
+    /// Resolve the scroll action for one direction into a panel action.
+    pub fn resolve(self, forward: bool) -> PanelAction {
+        match (self, forward) {
+            (ScrollAction::None, _) => PanelAction::None,
+            (ScrollAction::Track, true) => PanelAction::Next,
+            (ScrollAction::Track, false) => PanelAction::Previous,
+            (ScrollAction::Volume, true) => PanelAction::VolumeUp,
+            (ScrollAction::Volume, false) => PanelAction::VolumeDown,
+            (ScrollAction::Seek, true) => PanelAction::SeekForward,
+            (ScrollAction::Seek, false) => PanelAction::SeekBackward,
+            (ScrollAction::CyclePlayer, true) => PanelAction::CyclePlayer,
+            (ScrollAction::CyclePlayer, false) => PanelAction::CyclePlayerBack,
+        }
+    }
+}
+
+/// How volume slider positions map to volumes.
+///
+/// Positions use the same scale as volumes (1.0 = 100%), so the 100% mark sits
+/// at position 1.0 for every curve and boosted volumes extend past it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VolumeCurve {
+    Linear,
+    /// Volume is the cube of the position, close to perceived loudness
+    Cubic,
+    /// 40 dB of range spread evenly over the slider
+    Logarithmic,
+}
+
+impl VolumeCurve {
+    pub const ALL: [VolumeCurve; 3] = [
+        VolumeCurve::Linear,
+        VolumeCurve::Cubic,
+        VolumeCurve::Logarithmic,
+    ];
+
+    pub const LABELS: [&'static str; 3] = ["Linear", "Cubic", "Logarithmic"];
+
+    /// Span of the logarithmic curve in decades (20 dB each).
+    const LOG_DECADES: f64 = 2.0;
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|c| *c == self).unwrap_or(0)
+    }
+
+    pub fn to_volume(self, position: f64) -> f64 {
+        let position = position.max(0.0);
+        match self {
+            VolumeCurve::Linear => position,
+            VolumeCurve::Cubic => position.powi(3),
+            VolumeCurve::Logarithmic => {
+                let scale = 10f64.powf(Self::LOG_DECADES) - 1.0;
+                (10f64.powf(Self::LOG_DECADES * position) - 1.0) / scale
+            }
+        }
+    }
+
+    pub fn to_position(self, volume: f64) -> f64 {
+        let volume = volume.max(0.0);
+        match self {
+            VolumeCurve::Linear => volume,
+            VolumeCurve::Cubic => volume.cbrt(),
+            VolumeCurve::Logarithmic => {
+                let scale = 10f64.powf(Self::LOG_DECADES) - 1.0;
+                (volume * scale + 1.0).log10() / Self::LOG_DECADES
+            }
+        }
+    }
+}
+
+/// Where a player's volume is read from and set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VolumeSource {
+    /// The audio stream if one is matched to the player, else MPRIS
+    Auto,
+    Mpris,
+    Stream,
+}
+
+impl VolumeSource {
+    pub const ALL: [VolumeSource; 3] = [
+        VolumeSource::Auto,
+        VolumeSource::Mpris,
+        VolumeSource::Stream,
+    ];
+
+    pub const LABELS: [&'static str; 3] = ["Auto", "MPRIS", "Audio stream"];
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|s| *s == self).unwrap_or(0)
+    }
+}
+
+/// Click gestures that can be bound on the panel icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickGesture {
+    Left,
+    Middle,
+    Right,
+    Double,
+}
+
+/// Scroll axes that can be bound on the panel icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollAxis {
+    Vertical,
+    Horizontal,
+}
+
+/// A daily time window with a lower volume cap, e.g. 22:00–07:00.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuietHours {
+    pub enabled: bool,
+    /// Start and end in minutes after midnight; the window runs past
+    /// midnight when it ends before it starts
+    pub start_minutes: u32,
+    pub end_minutes: u32,
+    pub cap: f64,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start_minutes: 22 * 60,
+            end_minutes: 7 * 60,
+            cap: 0.3,
+        }
+    }
+}
+
+impl QuietHours {
+    /// Whether `time` falls in the window. Equal start and end make an empty
+    /// window.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let minutes = time.hour() * 60 + time.minute();
+        if self.start_minutes <= self.end_minutes {
+            (self.start_minutes..self.end_minutes).contains(&minutes)
+        } else {
+            minutes >= self.start_minutes || minutes < self.end_minutes
+        }
+    }
+}
+
+/// The spectrum visualizer under the album art.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpectrumSettings {
+    pub enabled: bool,
+    pub bar_count: u32,
+    /// How slowly bars fall back, 0.0 (not at all smoothed) to 0.95
+    pub smoothing: f64,
+    /// Redraws per second at most
+    pub max_fps: u32,
+}
+
+impl Default for SpectrumSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bar_count: 32,
+            smoothing: 0.7,
+            max_fps: 30,
+        }
+    }
+}
+
+/// The lowest volume cap that applies to a player at some time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeCap {
+    pub limit: f64,
+    /// Whether the limit comes from quiet hours
+    pub quiet_hours: bool,
+}
+
+/// Mouse and gesture bindings for the panel icon.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelBindings {
+    pub left_click: PanelAction,
+    pub middle_click: PanelAction,
+    pub right_click: PanelAction,
+    pub double_click: PanelAction,
+    pub scroll: ScrollAction,
+    pub horizontal_scroll: ScrollAction,
+    /// Volume change per step, as a fraction (0.05 = 5%)
+    pub volume_step: f64,
+    /// Seek offset per step in seconds
+    pub seek_step_secs: u32,
+}
+
+impl Default for PanelBindings {
+    fn default() -> Self {
+        Self {
+            left_click: PanelAction::TogglePopup,
+            middle_click: PanelAction::PlayPause,
+            right_click: PanelAction::ContextMenu,
+            double_click: PanelAction::None,
+            scroll: ScrollAction::Track,
+            horizontal_scroll: ScrollAction::None,
+            volume_step: 0.05,
+            seek_step_secs: 10,
+        }
+    }
+}
+
+impl PanelBindings {
+    pub fn click(&self, gesture: ClickGesture) -> PanelAction {
+        match gesture {
+            ClickGesture::Left => self.left_click,
+            ClickGesture::Middle => self.middle_click,
+            ClickGesture::Right => self.right_click,
+            ClickGesture::Double => self.double_click,
+        }
+    }
+
+    pub fn scroll(&self, axis: ScrollAxis) -> ScrollAction {
+        match axis {
+            ScrollAxis::Vertical => self.scroll,
+            ScrollAxis::Horizontal => self.horizontal_scroll,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     pub enabled_players: HashSet<String>,
     pub auto_detect_new_players: bool,
     pub selected_player: Option<String>,
     pub show_all_players: bool,
     pub hide_inactive_players: bool,
+    pub panel_bindings: PanelBindings,
+    /// Size limit of the on-disk album art cache in megabytes
+    pub art_cache_max_mb: u32,
+    /// Look up missing covers on MusicBrainz (sends artist and album online)
+    pub online_art_lookup: bool,
+    pub musicbrainz_url: String,
+    pub cover_art_archive_url: String,
+    /// Only show cached album art, never download
+    pub art_offline_mode: bool,
+    /// Refuse album art served over plain HTTP
+    pub art_https_only: bool,
+    /// Allow album art from loopback and LAN addresses
+    pub art_allow_private_networks: bool,
+    /// User-Agent sent with art requests; empty uses the built-in one
+    pub art_user_agent: String,
+    /// Folders local album art may be read from
+    pub art_allowed_paths: Vec<PathBuf>,
+    /// Folders local album art is never read from, even if allowed
+    pub art_denied_paths: Vec<PathBuf>,
+    /// Tint the popup, sliders and panel icon with colors from the album art
+    pub accent_from_art: bool,
+    /// Preferred output sink name per player identity
+    pub player_outputs: HashMap<String, String>,
+    pub volume_curve: VolumeCurve,
+    /// Let stream-backed volume sliders go up to 150%
+    pub volume_boost: bool,
+    /// Volume source per player identity; players not listed use `Auto`
+    pub volume_sources: HashMap<String, VolumeSource>,
+    /// Last volume per player identity, reapplied when the player appears
+    pub player_volumes: HashMap<String, f64>,
+    /// Fixed volume per player identity, used instead of the last volume
+    pub start_volumes: HashMap<String, f64>,
+    /// Highest volume any player may be set to
+    pub volume_cap: Option<f64>,
+    /// Highest volume per player identity
+    pub player_volume_caps: HashMap<String, f64>,
+    pub quiet_hours: QuietHours,
+    pub spectrum: SpectrumSettings,
 }
 
 impl Default for AppConfig {
@@ -21,10 +379,63 @@ impl Default for AppConfig {
             selected_player: None,
             show_all_players: false,
             hide_inactive_players: false,
+            panel_bindings: PanelBindings::default(),
+            art_cache_max_mb: DEFAULT_ART_CACHE_MAX_MB,
+            online_art_lookup: false,
+            musicbrainz_url: DEFAULT_MUSICBRAINZ_URL.to_string(),
+            cover_art_archive_url: DEFAULT_COVER_ART_ARCHIVE_URL.to_string(),
+            art_offline_mode: false,
+            art_https_only: false,
+            art_allow_private_networks: false,
+            art_user_agent: String::new(),
+            art_allowed_paths: paths::default_allowed_paths(),
+            art_denied_paths: Vec::new(),
+            accent_from_art: true,
+            player_outputs: HashMap::new(),
+            volume_curve: VolumeCurve::Linear,
+            volume_boost: false,
+            volume_sources: HashMap::new(),
+            player_volumes: HashMap::new(),
+            start_volumes: HashMap::new(),
+            volume_cap: None,
+            player_volume_caps: HashMap::new(),
+            quiet_hours: QuietHours::default(),
+            spectrum: SpectrumSettings::default(),
         }
     }
 }
 
+impl AppConfig {
+    /// The lowest of the global, per-player and quiet hours caps in effect
+    /// for a player at `time`; on a tie the cap that is not quiet hours.
+    pub fn volume_cap_at(&self, identity: &str, time: NaiveTime) -> Option<VolumeCap> {
+        let quiet_cap =
+            (self.quiet_hours.enabled && self.quiet_hours.contains(time)).then_some(VolumeCap {
+                limit: self.quiet_hours.cap,
+                quiet_hours: true,
+            });
+        [
+            self.volume_cap,
+            self.player_volume_caps.get(identity).copied(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|limit| VolumeCap {
+            limit,
+            quiet_hours: false,
+        })
+        .chain(quiet_cap)
+        .min_by(|a, b| a.limit.total_cmp(&b.limit))
+    }
+}
+
+/// Which of the album art folder lists a Settings action applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtPathList {
+    Allowed,
+    Denied,
+}
+
 pub struct ConfigManager {
     config: Config,
     app_config: AppConfig,
@@ -88,8 +499,435 @@ impl ConfigManager {
         self.save_config()
     }
 
+    pub fn get_panel_bindings(&self) -> PanelBindings {
+        self.app_config.panel_bindings
+    }
+
+    pub fn set_click_binding(
+        &mut self,
+        gesture: ClickGesture,
+        action: PanelAction,
+    ) -> anyhow::Result<()> {
+        let bindings = &mut self.app_config.panel_bindings;
+        match gesture {
+            ClickGesture::Left => bindings.left_click = action,
+            ClickGesture::Middle => bindings.middle_click = action,
+            ClickGesture::Right => bindings.right_click = action,
+            ClickGesture::Double => bindings.double_click = action,
+        }
+        self.save_config()
+    }
+
+    pub fn set_scroll_binding(
+        &mut self,
+        axis: ScrollAxis,
+        action: ScrollAction,
+    ) -> anyhow::Result<()> {
+        let bindings = &mut self.app_config.panel_bindings;
+        match axis {
+            ScrollAxis::Vertical => bindings.scroll = action,
+            ScrollAxis::Horizontal => bindings.horizontal_scroll = action,
+        }
+        self.save_config()
+    }
+
+    pub fn set_volume_step(&mut self, step: f64) -> anyhow::Result<()> {
+        self.app_config.panel_bindings.volume_step = step.clamp(0.01, 0.25);
+        self.save_config()
+    }
+
+    pub fn set_seek_step_secs(&mut self, secs: u32) -> anyhow::Result<()> {
+        self.app_config.panel_bindings.seek_step_secs = secs.clamp(1, 120);
+        self.save_config()
+    }
+
+    pub fn get_art_cache_max_mb(&self) -> u32 {
+        self.app_config.art_cache_max_mb
+    }
+
+    pub fn get_online_art_lookup(&self) -> bool {
+        self.app_config.online_art_lookup
+    }
+
+    pub fn set_online_art_lookup(&mut self, enabled: bool) -> anyhow::Result<()> {
+        self.app_config.online_art_lookup = enabled;
+        self.save_config()
+    }
+
+    pub fn get_musicbrainz_url(&self) -> &str {
+        &self.app_config.musicbrainz_url
+    }
+
+    pub fn get_cover_art_archive_url(&self) -> &str {
+        &self.app_config.cover_art_archive_url
+    }
+
+    pub fn get_art_fetch_policy(&self) -> FetchPolicy {
+        let user_agent = if self.app_config.art_user_agent.trim().is_empty() {
+            DEFAULT_USER_AGENT.to_string()
+        } else {
+            self.app_config.art_user_agent.clone()
+        };
+        FetchPolicy {
+            offline: self.app_config.art_offline_mode,
+            https_only: self.app_config.art_https_only,
+            allow_private_networks: self.app_config.art_allow_private_networks,
+            user_agent,
+        }
+    }
+
+    pub fn set_art_offline_mode(&mut self, enabled: bool) -> anyhow::Result<()> {
+        self.app_config.art_offline_mode = enabled;
+        self.save_config()
+    }
+
+    pub fn set_art_https_only(&mut self, enabled: bool) -> anyhow::Result<()> {
+        self.app_config.art_https_only = enabled;
+        self.save_config()
+    }
+
+    pub fn set_art_allow_private_networks(&mut self, enabled: bool) -> anyhow::Result<()> {
+        self.app_config.art_allow_private_networks = enabled;
+        self.save_config()
+    }
+
+    pub fn get_art_path_policy(&self) -> PathPolicy {
+        PathPolicy {
+            allowed: self.app_config.art_allowed_paths.clone(),
+            denied: self.app_config.art_denied_paths.clone(),
+        }
+    }
+
+    pub fn get_art_paths(&self, list: ArtPathList) -> &[PathBuf] {
+        match list {
+            ArtPathList::Allowed => &self.app_config.art_allowed_paths,
+            ArtPathList::Denied => &self.app_config.art_denied_paths,
+        }
+    }
+
+    fn art_paths_mut(&mut self, list: ArtPathList) -> &mut Vec<PathBuf> {
+        match list {
+            ArtPathList::Allowed => &mut self.app_config.art_allowed_paths,
+            ArtPathList::Denied => &mut self.app_config.art_denied_paths,
+        }
+    }
+
+    pub fn add_art_path(&mut self, list: ArtPathList, path: PathBuf) -> anyhow::Result<()> {
+        let paths = self.art_paths_mut(list);
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+        self.save_config()
+    }
+
+    pub fn remove_art_path(&mut self, list: ArtPathList, path: &Path) -> anyhow::Result<()> {
+        self.art_paths_mut(list).retain(|p| p != path);
+        self.save_config()
+    }
+
+    pub fn reset_art_allowed_paths(&mut self) -> anyhow::Result<()> {
+        self.app_config.art_allowed_paths = paths::default_allowed_paths();
+        self.save_config()
+    }
+
+    pub fn get_accent_from_art(&self) -> bool {
+        self.app_config.accent_from_art
+    }
+
+    pub fn set_accent_from_art(&mut self, enabled: bool) -> anyhow::Result<()> {
+        self.app_config.accent_from_art = enabled;
+        self.save_config()
+    }
+
+    pub fn get_player_output(&self, identity: &str) -> Option<&str> {
+        self.app_config
+            .player_outputs
+            .get(identity)
+            .map(String::as_str)
+    }
+
+    /// Remember the output for a player; `None` makes it follow the default.
+    pub fn set_player_output(
+        &mut self,
+        identity: &str,
+        sink_name: Option<String>,
+    ) -> anyhow::Result<()> {
+        match sink_name {
+            Some(sink_name) => {
+                self.app_config
+                    .player_outputs
+                    .insert(identity.to_string(), sink_name);
+            }
+            None => {
+                self.app_config.player_outputs.remove(identity);
+            }
+        }
+        self.save_config()
+    }
+
+    pub fn get_volume_curve(&self) -> VolumeCurve {
+        self.app_config.volume_curve
+    }
+
+    pub fn set_volume_curve(&mut self, curve: VolumeCurve) -> anyhow::Result<()> {
+        self.app_config.volume_curve = curve;
+        self.save_config()
+    }
+
+    pub fn get_volume_boost(&self) -> bool {
+        self.app_config.volume_boost
+    }
+
+    pub fn set_volume_boost(&mut self, enabled: bool) -> anyhow::Result<()> {
+        self.app_config.volume_boost = enabled;
+        self.save_config()
+    }
+
+    pub fn get_volume_sources(&self) -> &HashMap<String, VolumeSource> {
+        &self.app_config.volume_sources
+    }
+
+    pub fn set_volume_source(
+        &mut self,
+        identity: &str,
+        source: VolumeSource,
+    ) -> anyhow::Result<()> {
+        if source == VolumeSource::Auto {
+            self.app_config.volume_sources.remove(identity);
+        } else {
+            self.app_config
+                .volume_sources
+                .insert(identity.to_string(), source);
+        }
+        self.save_config()
+    }
+
+    /// Remember the last volume of several players with a single write.
+    pub fn set_player_volumes(
+        &mut self,
+        volumes: impl IntoIterator<Item = (String, f64)>,
+    ) -> anyhow::Result<()> {
+        self.app_config.player_volumes.extend(volumes);
+        self.save_config()
+    }
+
+    pub fn get_start_volume(&self, identity: &str) -> Option<f64> {
+        self.app_config.start_volumes.get(identity).copied()
+    }
+
+    /// Set a fixed start volume for a player; `None` reapplies its last volume.
+    pub fn set_start_volume(&mut self, identity: &str, volume: Option<f64>) -> anyhow::Result<()> {
+        match volume {
+            Some(volume) => {
+                self.app_config
+                    .start_volumes
+                    .insert(identity.to_string(), volume);
+            }
+            None => {
+                self.app_config.start_volumes.remove(identity);
+            }
+        }
+        self.save_config()
+    }
+
+    /// Volume a player is set to when it appears: its start volume if one
+    /// is set, else the volume it had last.
+    pub fn get_initial_volume(&self, identity: &str) -> Option<f64> {
+        self.get_start_volume(identity)
+            .or_else(|| self.app_config.player_volumes.get(identity).copied())
+    }
+
+    pub fn get_player_volume(&self, identity: &str) -> Option<f64> {
+        self.app_config.player_volumes.get(identity).copied()
+    }
+
+    pub fn get_volume_cap(&self) -> Option<f64> {
+        self.app_config.volume_cap
+    }
+
+    pub fn set_volume_cap(&mut self, cap: Option<f64>) -> anyhow::Result<()> {
+        self.app_config.volume_cap = cap;
+        self.save_config()
+    }
+
+    pub fn get_player_volume_cap(&self, identity: &str) -> Option<f64> {
+        self.app_config.player_volume_caps.get(identity).copied()
+    }
+
+    pub fn set_player_volume_cap(
+        &mut self,
+        identity: &str,
+        cap: Option<f64>,
+    ) -> anyhow::Result<()> {
+        match cap {
+            Some(cap) => {
+                self.app_config
+                    .player_volume_caps
+                    .insert(identity.to_string(), cap);
+            }
+            None => {
+                self.app_config.player_volume_caps.remove(identity);
+            }
+        }
+        self.save_config()
+    }
+
+    pub fn get_quiet_hours(&self) -> QuietHours {
+        self.app_config.quiet_hours
+    }
+
+    pub fn set_quiet_hours(&mut self, quiet_hours: QuietHours) -> anyhow::Result<()> {
+        self.app_config.quiet_hours = quiet_hours;
+        self.save_config()
+    }
+
+    pub fn get_spectrum(&self) -> SpectrumSettings {
+        self.app_config.spectrum
+    }
+
+    pub fn set_spectrum(&mut self, spectrum: SpectrumSettings) -> anyhow::Result<()> {
+        self.app_config.spectrum = SpectrumSettings {
+            bar_count: spectrum.bar_count.clamp(8, 64),
+            smoothing: spectrum.smoothing.clamp(0.0, 0.95),
+            max_fps: spectrum.max_fps.clamp(10, 60),
+            ..spectrum
+        };
+        self.save_config()
+    }
+
+    /// The lowest of the global, per-player and quiet hours caps in effect
+    /// for a player at `time`.
+    pub fn volume_cap_at(&self, identity: &str, time: NaiveTime) -> Option<VolumeCap> {
+        self.app_config.volume_cap_at(identity, time)
+    }
+
     fn save_config(&self) -> anyhow::Result<()> {
         self.config.set("config", &self.app_config)?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn quiet_hours(start: (u32, u32), end: (u32, u32), cap: f64) -> QuietHours {
+        QuietHours {
+            enabled: true,
+            start_minutes: start.0 * 60 + start.1,
+            end_minutes: end.0 * 60 + end.1,
+            cap,
+        }
+    }
+
+    #[test]
+    fn quiet_hours_within_a_day() {
+        let window = quiet_hours((13, 0), (15, 30), 0.3);
+        assert!(!window.contains(at(12, 59)));
+        assert!(window.contains(at(13, 0)));
+        assert!(window.contains(at(15, 29)));
+        assert!(!window.contains(at(15, 30)));
+    }
+
+    #[test]
+    fn quiet_hours_across_midnight() {
+        let window = quiet_hours((22, 0), (7, 0), 0.3);
+        assert!(!window.contains(at(21, 59)));
+        assert!(window.contains(at(22, 0)));
+        assert!(window.contains(at(23, 59)));
+        assert!(window.contains(at(0, 0)));
+        assert!(window.contains(at(6, 59)));
+        assert!(!window.contains(at(7, 0)));
+        assert!(!window.contains(at(12, 0)));
+    }
+
+    #[test]
+    fn quiet_hours_with_equal_start_and_end_are_empty() {
+        let window = quiet_hours((8, 0), (8, 0), 0.3);
+        for time in [at(0, 0), at(7, 59), at(8, 0), at(8, 1), at(23, 59)] {
+            assert!(!window.contains(time), "{time}");
+        }
+    }
+
+    #[test]
+    fn volume_cap_is_the_lowest_in_effect() {
+        let mut config = AppConfig {
+            volume_cap: Some(0.8),
+            quiet_hours: quiet_hours((22, 0), (7, 0), 0.3),
+            ..AppConfig::default()
+        };
+        config.player_volume_caps.insert("Spotify".to_string(), 0.5);
+
+        let cap = |identity, time| config.volume_cap_at(identity, time);
+        assert_eq!(
+            cap("Firefox", at(12, 0)),
+            Some(VolumeCap {
+                limit: 0.8,
+                quiet_hours: false
+            })
+        );
+        assert_eq!(
+            cap("Spotify", at(12, 0)),
+            Some(VolumeCap {
+                limit: 0.5,
+                quiet_hours: false
+            })
+        );
+        assert_eq!(
+            cap("Spotify", at(23, 0)),
+            Some(VolumeCap {
+                limit: 0.3,
+                quiet_hours: true
+            })
+        );
+    }
+
+    #[test]
+    fn volume_cap_prefers_the_regular_cap_on_a_tie() {
+        let config = AppConfig {
+            volume_cap: Some(0.3),
+            quiet_hours: quiet_hours((22, 0), (7, 0), 0.3),
+            ..AppConfig::default()
+        };
+        assert_eq!(
+            config.volume_cap_at("Firefox", at(23, 0)),
+            Some(VolumeCap {
+                limit: 0.3,
+                quiet_hours: false
+            })
+        );
+    }
+
+    #[test]
+    fn no_volume_cap_by_default() {
+        let mut config = AppConfig::default();
+        assert_eq!(config.volume_cap_at("Firefox", at(23, 0)), None);
+
+        // Quiet hours only apply while enabled
+        config.quiet_hours = QuietHours {
+            enabled: false,
+            ..quiet_hours((22, 0), (7, 0), 0.3)
+        };
+        assert_eq!(config.volume_cap_at("Firefox", at(23, 0)), None);
+    }
+
+    #[test]
+    fn volume_curves_round_trip() {
+        for curve in VolumeCurve::ALL {
+            for step in 0..=15 {
+                let position = f64::from(step) / 10.0;
+                let volume = curve.to_volume(position);
+                assert!(
+                    (curve.to_position(volume) - position).abs() < 1e-9,
+                    "{curve:?} at {position}"
+                );
+            }
+            assert!(curve.to_volume(0.0).abs() < 1e-9, "{curve:?}");
+            assert!((curve.to_volume(1.0) - 1.0).abs() < 1e-9, "{curve:?}");
+        }
+    }
+}
@@ -1,21 +1,50 @@
-use crate::audio::AudioController;
+use crate::art;
+use crate::audio::{process, AudioController, AudioLevel, AudioSink, AudioSinkInput, StreamMatch};
+use crate::config::VolumeSource;
 use anyhow::Result;
-use mpris::{PlaybackStatus, Player, PlayerFinder};
+use mpris::{LoopStatus, PlaybackStatus, Player, PlayerFinder};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Well-known bus name prefix of MPRIS players.
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+
+/// One of the audio streams a player's volume is read from and set on.
+#[derive(Debug, Clone)]
+pub struct PlayerStream {
+    pub index: u32,
+    pub application_name: String,
+    pub volume: f64,
+    pub muted: bool,
+    /// Index of the sink the stream plays on
+    pub sink: u32,
+}
 
 #[derive(Debug, Clone)]
 pub struct PlayerInfo {
     pub title: String,
     pub artist: String,
+    pub album: String,
     pub status: PlaybackStatus,
     pub volume: f64,
     pub art_url: Option<String>,
     pub bus_name: String,
     pub identity: String,
     pub can_control_volume: bool,
+    /// All of the player's audio streams are muted on the sound server, or
+    /// a player without streams was muted by setting its volume to 0
+    pub muted: bool,
+    /// Audio streams matched to the player, sorted by index
+    pub streams: Vec<PlayerStream>,
+    /// How `streams` were attributed to the player
+    pub stream_match: Option<StreamMatch>,
+    /// Where `volume` comes from: `Mpris` or `Stream`, never `Auto`
+    pub volume_source: VolumeSource,
+    pub shuffle: bool,
+    pub loop_status: LoopStatus,
 }
 
 #[derive(Debug, Clone)]
@@ -29,12 +58,19 @@ impl Default for PlayerInfo {
         Self {
             title: "No music playing".to_string(),
             artist: String::new(),
+            album: String::new(),
             status: PlaybackStatus::Stopped,
             volume: 0.5,
             art_url: None,
             bus_name: String::new(),
             identity: String::new(),
             can_control_volume: true,
+            muted: false,
+            streams: Vec::new(),
+            stream_match: None,
+            volume_source: VolumeSource::Mpris,
+            shuffle: false,
+            loop_status: LoopStatus::None,
         }
     }
 }
@@ -45,6 +81,14 @@ pub struct MusicController {
     discovered_players: Rc<RefCell<HashMap<String, DiscoveredPlayer>>>,
     all_players: Rc<RefCell<HashMap<String, Player>>>,
     audio_controller: Option<Arc<AudioController>>,
+    /// Session bus connection for process ID lookups
+    dbus_connection: Option<Rc<dbus::blocking::Connection>>,
+    /// Process IDs owning player bus names, keyed by bus name
+    process_ids: Rc<RefCell<HashMap<String, u32>>>,
+    /// Volumes to restore for players muted through MPRIS, keyed by bus name
+    muted_volumes: Rc<RefCell<HashMap<String, f64>>>,
+    /// Configured volume source per player identity
+    volume_sources: Rc<RefCell<HashMap<String, VolumeSource>>>,
 }
 
 impl MusicController {
@@ -56,6 +100,13 @@ impl MusicController {
             discovered_players: Rc::new(RefCell::new(HashMap::new())),
             all_players: Rc::new(RefCell::new(HashMap::new())),
             audio_controller: Some(audio_controller),
+            dbus_connection: dbus::blocking::Connection::new_session()
+                .map_err(|e| eprintln!("Failed to connect to the session bus: {e}"))
+                .ok()
+                .map(Rc::new),
+            process_ids: Rc::new(RefCell::new(HashMap::new())),
+            muted_volumes: Rc::new(RefCell::new(HashMap::new())),
+            volume_sources: Rc::new(RefCell::new(HashMap::new())),
         }
     }
 
@@ -67,26 +118,29 @@ impl MusicController {
         discovered_borrow.clear();
         all_players_borrow.clear();
 
-        // Try to get all players
-        if let Ok(players) = player_finder.find_all() {
-            for player in players {
-                let identity = player.identity();
-                let bus_name = player.bus_name_player_name_part();
-                let is_active = player
-                    .get_playback_status()
-                    .unwrap_or(PlaybackStatus::Stopped)
-                    == PlaybackStatus::Playing;
-
-                discovered_borrow.insert(
-                    identity.to_string(),
-                    DiscoveredPlayer {
-                        identity: identity.to_string(),
-                        is_active,
-                    },
-                );
-
-                all_players_borrow.insert(bus_name.to_string(), player);
-            }
+        // Skip players that fail to answer rather than losing all of them
+        let players = player_finder
+            .iter_players()
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok);
+        for player in players {
+            let identity = player.identity();
+            let bus_name = player.bus_name_player_name_part();
+            let is_active = player
+                .get_playback_status()
+                .unwrap_or(PlaybackStatus::Stopped)
+                == PlaybackStatus::Playing;
+
+            discovered_borrow.insert(
+                identity.to_string(),
+                DiscoveredPlayer {
+                    identity: identity.to_string(),
+                    is_active,
+                },
+            );
+
+            all_players_borrow.insert(bus_name.to_string(), player);
         }
 
         Ok(())
@@ -123,18 +177,105 @@ impl MusicController {
         Ok(())
     }
 
+    /// Process ID owning a player's D-Bus name, used to find its audio
+    /// streams. Cached until the process exits.
+    fn player_process_id(&self, player: &Player) -> Option<u32> {
+        let bus_name = player.bus_name();
+        if let Some(&pid) = self.process_ids.borrow().get(bus_name) {
+            if process::is_running(pid) {
+                return Some(pid);
+            }
+        }
+
+        let connection = self.dbus_connection.as_ref()?;
+        let proxy = connection.with_proxy(
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            Duration::from_millis(500),
+        );
+        let (pid,): (u32,) = proxy
+            .method_call(
+                "org.freedesktop.DBus",
+                "GetConnectionUnixProcessID",
+                (bus_name,),
+            )
+            .ok()?;
+
+        self.process_ids
+            .borrow_mut()
+            .insert(bus_name.to_string(), pid);
+        Some(pid)
+    }
+
+    /// Notifier for audio stream changes pushed by the sound server.
+    pub fn audio_changes(&self) -> Option<Arc<tokio::sync::Notify>> {
+        self.audio_controller
+            .as_ref()
+            .and_then(|audio_ctrl| audio_ctrl.changes())
+    }
+
     pub fn get_discovered_players(&self) -> Vec<DiscoveredPlayer> {
         self.discovered_players.borrow().values().cloned().collect()
     }
 
-    /// Extract player info from an MPRIS player, resolving volume via audio
-    /// controller fallback for browsers that don't support MPRIS volume.
+    /// Bus names of the players on the session bus, as in
+    /// [`PlayerInfo::bus_name`]. A single D-Bus call, unlike discovery.
+    pub fn player_bus_names(&self) -> Option<HashSet<String>> {
+        let connection = self.dbus_connection.as_ref()?;
+        let proxy = connection.with_proxy(
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            Duration::from_millis(500),
+        );
+        let (names,): (Vec<String>,) = proxy
+            .method_call("org.freedesktop.DBus", "ListNames", ())
+            .map_err(|e| eprintln!("Failed to list D-Bus names: {e}"))
+            .ok()?;
+        Some(
+            names
+                .iter()
+                .filter_map(|name| name.strip_prefix(MPRIS_PREFIX))
+                .map(ToString::to_string)
+                .collect(),
+        )
+    }
+
+    /// Use the configured volume sources, keyed by player identity.
+    pub fn set_volume_sources(&self, sources: HashMap<String, VolumeSource>) {
+        *self.volume_sources.borrow_mut() = sources;
+    }
+
+    /// The player's audio streams, if any are matched.
+    fn player_streams(&self, player: &Player) -> Option<(Vec<AudioSinkInput>, StreamMatch)> {
+        let audio_ctrl = self.audio_controller.as_ref()?;
+        let process_id = self.player_process_id(player);
+        audio_ctrl.find_player_streams(process_id, player.identity())
+    }
+
+    /// Resolve the player's volume source. `Auto` picks the audio stream when
+    /// one is matched, since browsers often ignore MPRIS volume, and MPRIS
+    /// otherwise. Reading and setting the volume both go through this.
+    fn volume_source(&self, player: &Player, has_streams: bool) -> VolumeSource {
+        let configured = self
+            .volume_sources
+            .borrow()
+            .get(player.identity())
+            .copied()
+            .unwrap_or(VolumeSource::Auto);
+        match configured {
+            VolumeSource::Auto if has_streams => VolumeSource::Stream,
+            VolumeSource::Auto => VolumeSource::Mpris,
+            source => source,
+        }
+    }
+
+    /// Extract player info from an MPRIS player, reading the volume from its
+    /// resolved volume source.
     fn extract_player_info(&self, player: &Player, bus_name: String) -> PlayerInfo {
         let metadata = player.get_metadata().unwrap_or_default();
         let status = player
             .get_playback_status()
             .unwrap_or(PlaybackStatus::Stopped);
-        let mut volume = player.get_volume().unwrap_or(0.5);
 
         let title = metadata
             .title()
@@ -144,42 +285,83 @@ impl MusicController {
             .artists()
             .map_or_else(|| "Unknown Artist".to_string(), |a| a.join(", "));
 
-        let art_url = metadata.art_url().map(ToString::to_string);
+        let album = metadata.album_name().unwrap_or_default().to_string();
+
+        // Players like mpv publish no artUrl; fall back to the local track file
+        // so the art loader can use its embedded cover
+        let art_url = metadata
+            .art_url()
+            .or_else(|| metadata.url().filter(|url| art::is_audio_track_url(url)))
+            .map(ToString::to_string);
         let identity = player.identity().to_string();
+        let shuffle = player.get_shuffle().unwrap_or(false);
+        let loop_status = player.get_loop_status().unwrap_or(LoopStatus::None);
 
-        // For browsers, get actual volume from PulseAudio/PipeWire
-        if let Some(ref audio_ctrl) = self.audio_controller {
-            if let Some(sink_input) = audio_ctrl.find_sink_input_by_name(&identity) {
-                volume = sink_input.volume;
-            }
-        }
+        let (sink_inputs, stream_match) = match self.player_streams(player) {
+            Some((sink_inputs, matched_by)) => (sink_inputs, Some(matched_by)),
+            None => (Vec::new(), None),
+        };
+        let volume_source = self.volume_source(player, !sink_inputs.is_empty());
+
+        let (volume, muted, can_control_volume) = match volume_source {
+            // The player is as loud as its loudest stream
+            VolumeSource::Stream => (
+                sink_inputs.iter().map(|s| s.volume).fold(0.0, f64::max),
+                !sink_inputs.is_empty() && sink_inputs.iter().all(|s| s.muted),
+                !sink_inputs.is_empty(),
+            ),
+            _ => (
+                player.get_volume().unwrap_or(0.5),
+                self.muted_volumes.borrow().contains_key(player.bus_name()),
+                true,
+            ),
+        };
+
+        let streams = sink_inputs
+            .into_iter()
+            .map(|s| PlayerStream {
+                index: s.index,
+                application_name: s.application_name,
+                volume: s.volume,
+                muted: s.muted,
+                sink: s.sink,
+            })
+            .collect();
 
         PlayerInfo {
             title,
             artist,
+            album,
             status,
             volume,
             art_url,
             bus_name,
             identity,
-            can_control_volume: true,
+            can_control_volume,
+            muted,
+            streams,
+            stream_match,
+            volume_source,
+            shuffle,
+            loop_status,
         }
     }
 
     pub fn get_player_info(&self) -> PlayerInfo {
         let player_borrow = self.player.borrow();
 
-        let Some(ref player) = *player_borrow else {
-            return PlayerInfo::default();
-        };
-
-        // Refresh audio sinks before extracting info
+        // Refresh audio sinks before extracting info (and for the mixer, even
+        // without a player)
         if let Some(ref audio_ctrl) = self.audio_controller {
             if let Err(e) = audio_ctrl.refresh_sink_inputs() {
                 eprintln!("Failed to refresh audio sink inputs: {e}");
             }
         }
 
+        let Some(ref player) = *player_borrow else {
+            return PlayerInfo::default();
+        };
+
         let bus_name = player.bus_name_player_name_part().to_string();
         self.extract_player_info(player, bus_name)
     }
@@ -227,24 +409,93 @@ impl MusicController {
         players_info
     }
 
-    /// Set volume on a player, trying MPRIS first, then audio controller fallback.
+    /// Set volume on a player through its resolved volume source.
     fn set_volume_on_player(&self, player: &Player, volume: f64) -> Result<()> {
-        // Try MPRIS first
-        if player.set_volume(volume).is_ok() {
+        let streams = self.refreshed_player_streams(player);
+        if self.volume_source(player, !streams.is_empty()) == VolumeSource::Stream {
+            if let Some(ref audio_ctrl) = self.audio_controller {
+                audio_ctrl.set_streams_volume(&streams, volume)?;
+            }
             return Ok(());
         }
 
-        // If MPRIS fails, try audio controller (for browsers)
+        // MPRIS volumes are not boosted past 100%
+        player.set_volume(volume.min(1.0))?;
+        // Moving the slider unmutes a player muted through its volume
+        self.muted_volumes.borrow_mut().remove(player.bus_name());
+        Ok(())
+    }
+
+    /// The player's audio streams after refreshing the stream table, for
+    /// acting on them.
+    fn refreshed_player_streams(&self, player: &Player) -> Vec<AudioSinkInput> {
         if let Some(ref audio_ctrl) = self.audio_controller {
-            let identity = player.identity();
             if let Err(e) = audio_ctrl.refresh_sink_inputs() {
                 eprintln!("Failed to refresh audio sink inputs: {e}");
             }
-            if let Some(sink_input) = audio_ctrl.find_sink_input_by_name(identity) {
-                audio_ctrl.set_sink_input_volume(sink_input.index, volume)?;
+        }
+        self.player_streams(player)
+            .map(|(streams, _)| streams)
+            .unwrap_or_default()
+    }
+
+    /// Mute a player through its resolved volume source: stream mute keeps
+    /// the stream volume, MPRIS volume is set to 0 and restored later.
+    fn set_mute_on_player(&self, player: &Player, muted: bool) -> Result<()> {
+        let streams = self.refreshed_player_streams(player);
+        if self.volume_source(player, !streams.is_empty()) == VolumeSource::Stream {
+            if let Some(ref audio_ctrl) = self.audio_controller {
+                for stream in &streams {
+                    audio_ctrl.set_sink_input_mute(stream.index, muted)?;
+                }
+            }
+            return Ok(());
+        }
+
+        let bus_name = player.bus_name();
+        if muted {
+            if self.muted_volumes.borrow().contains_key(bus_name) {
+                return Ok(());
             }
+            let volume = player.get_volume()?;
+            player.set_volume(0.0)?;
+            self.muted_volumes
+                .borrow_mut()
+                .insert(bus_name.to_string(), volume);
+        } else if let Some(volume) = self.muted_volumes.borrow_mut().remove(bus_name) {
+            player.set_volume(volume)?;
         }
+        Ok(())
+    }
+
+    fn toggle_shuffle_on_player(player: &Player) -> Result<()> {
+        let shuffle = player.get_shuffle()?;
+        player.set_shuffle(!shuffle)?;
+        Ok(())
+    }
 
+    /// Cycle the loop status: None -> Playlist -> Track -> None.
+    fn cycle_loop_status_on_player(player: &Player) -> Result<()> {
+        let next = match player.get_loop_status()? {
+            LoopStatus::None => LoopStatus::Playlist,
+            LoopStatus::Playlist => LoopStatus::Track,
+            LoopStatus::Track => LoopStatus::None,
+        };
+        player.set_loop_status(next)?;
+        Ok(())
+    }
+
+    /// Pause every MPRIS player that is currently playing, not only the
+    /// controlled ones.
+    pub fn pause_all(&self) -> Result<()> {
+        let player_finder = PlayerFinder::new()?;
+        for player in player_finder.find_all().unwrap_or_default() {
+            if player.get_playback_status().ok() == Some(PlaybackStatus::Playing) {
+                if let Err(e) = player.pause() {
+                    eprintln!("Failed to pause '{}': {e}", player.identity());
+                }
+            }
+        }
         Ok(())
     }
 
@@ -282,6 +533,48 @@ impl MusicController {
         Ok(())
     }
 
+    pub fn set_mute(&self, muted: bool) -> Result<()> {
+        let player_borrow = self.player.borrow();
+        if let Some(ref player) = *player_borrow {
+            self.set_mute_on_player(player, muted)?;
+        }
+        Ok(())
+    }
+
+    pub fn toggle_shuffle(&self) -> Result<()> {
+        let player_borrow = self.player.borrow();
+        if let Some(ref player) = *player_borrow {
+            Self::toggle_shuffle_on_player(player)?;
+        }
+        Ok(())
+    }
+
+    pub fn cycle_loop_status(&self) -> Result<()> {
+        let player_borrow = self.player.borrow();
+        if let Some(ref player) = *player_borrow {
+            Self::cycle_loop_status_on_player(player)?;
+        }
+        Ok(())
+    }
+
+    /// Ask the player to bring its own window to the front.
+    pub fn raise(&self) -> Result<()> {
+        let player_borrow = self.player.borrow();
+        if let Some(ref player) = *player_borrow {
+            player.raise()?;
+        }
+        Ok(())
+    }
+
+    /// Seek relative to the current position; negative offsets seek backwards.
+    pub fn seek(&self, offset_micros: i64) -> Result<()> {
+        let player_borrow = self.player.borrow();
+        if let Some(ref player) = *player_borrow {
+            player.seek(offset_micros)?;
+        }
+        Ok(())
+    }
+
     // --- Multi-player controls (operate on self.all_players by bus_name) ---
 
     pub fn play_pause_player(&self, bus_name: &str) -> Result<()> {
@@ -315,4 +608,134 @@ impl MusicController {
         }
         Ok(())
     }
+
+    pub fn set_mute_player(&self, bus_name: &str, muted: bool) -> Result<()> {
+        let all_players_borrow = self.all_players.borrow();
+        if let Some(player) = all_players_borrow.get(bus_name) {
+            self.set_mute_on_player(player, muted)?;
+        }
+        Ok(())
+    }
+
+    /// Set the volume of a single audio stream, from the per-stream view.
+    pub fn set_stream_volume(&self, index: u32, volume: f64) -> Result<()> {
+        if let Some(ref audio_ctrl) = self.audio_controller {
+            audio_ctrl.set_sink_input_volume(index, volume)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_stream_mute(&self, index: u32, muted: bool) -> Result<()> {
+        if let Some(ref audio_ctrl) = self.audio_controller {
+            audio_ctrl.set_sink_input_mute(index, muted)?;
+        }
+        Ok(())
+    }
+
+    /// Output devices streams can be moved to.
+    pub fn sinks(&self) -> Vec<AudioSink> {
+        self.audio_controller
+            .as_ref()
+            .map(|audio_ctrl| audio_ctrl.sinks())
+            .unwrap_or_default()
+    }
+
+    /// Every application stream, for the mixer.
+    pub fn sink_inputs(&self) -> Vec<AudioSinkInput> {
+        self.audio_controller
+            .as_ref()
+            .map(|audio_ctrl| audio_ctrl.sink_inputs())
+            .unwrap_or_default()
+    }
+
+    /// Name of the default output device, once known.
+    pub fn default_sink(&self) -> Option<String> {
+        self.audio_controller
+            .as_ref()
+            .and_then(|audio_ctrl| audio_ctrl.default_sink())
+    }
+
+    pub fn set_sink_volume(&self, index: u32, volume: f64) -> Result<()> {
+        if let Some(ref audio_ctrl) = self.audio_controller {
+            audio_ctrl.set_sink_volume(index, volume)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_sink_mute(&self, index: u32, muted: bool) -> Result<()> {
+        if let Some(ref audio_ctrl) = self.audio_controller {
+            audio_ctrl.set_sink_mute(index, muted)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_default_sink(&self, sink_name: &str) -> Result<()> {
+        if let Some(ref audio_ctrl) = self.audio_controller {
+            audio_ctrl.set_default_sink(sink_name)?;
+        }
+        Ok(())
+    }
+
+    /// Keep the levels of exactly these audio streams current.
+    pub fn monitor_streams(&self, indices: &[u32]) -> Result<()> {
+        if let Some(ref audio_ctrl) = self.audio_controller {
+            audio_ctrl.monitor_streams(indices)?;
+        }
+        Ok(())
+    }
+
+    /// Latest level of each monitored audio stream.
+    pub fn stream_levels(&self) -> HashMap<u32, AudioLevel> {
+        self.audio_controller
+            .as_ref()
+            .map(|audio_ctrl| audio_ctrl.levels())
+            .unwrap_or_default()
+    }
+
+    /// The most recent samples of a monitored audio stream.
+    pub fn stream_samples(&self, index: u32) -> Option<Vec<f32>> {
+        self.audio_controller.as_ref()?.samples(index)
+    }
+
+    /// Move audio streams to the sink with this name.
+    pub fn move_streams(&self, indices: &[u32], sink_name: &str) -> Result<()> {
+        if let Some(ref audio_ctrl) = self.audio_controller {
+            for &index in indices {
+                audio_ctrl.move_sink_input(index, sink_name)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn seek_player(&self, bus_name: &str, offset_micros: i64) -> Result<()> {
+        let all_players_borrow = self.all_players.borrow();
+        if let Some(player) = all_players_borrow.get(bus_name) {
+            player.seek(offset_micros)?;
+        }
+        Ok(())
+    }
+
+    pub fn toggle_shuffle_player(&self, bus_name: &str) -> Result<()> {
+        let all_players_borrow = self.all_players.borrow();
+        if let Some(player) = all_players_borrow.get(bus_name) {
+            Self::toggle_shuffle_on_player(player)?;
+        }
+        Ok(())
+    }
+
+    pub fn cycle_loop_status_player(&self, bus_name: &str) -> Result<()> {
+        let all_players_borrow = self.all_players.borrow();
+        if let Some(player) = all_players_borrow.get(bus_name) {
+            Self::cycle_loop_status_on_player(player)?;
+        }
+        Ok(())
+    }
+
+    pub fn raise_player(&self, bus_name: &str) -> Result<()> {
+        let all_players_borrow = self.all_players.borrow();
+        if let Some(player) = all_players_borrow.get(bus_name) {
+            player.raise()?;
+        }
+        Ok(())
+    }
 }